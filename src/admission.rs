@@ -0,0 +1,181 @@
+use std::cmp;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash, Hasher};
+
+// Counters saturate at this value, like the 4 bit counters of the
+// original TinyLFU design
+const MAX_COUNT: u8 = 15;
+
+// The number of counters each key maps to in the sketch
+const DEPTH: u64 = 4;
+
+// An approximate frequency count for every key ever seen, using a
+// fixed amount of memory. Each key increments one counter in each of
+// DEPTH rows; its estimate is the smallest of those counters, which
+// can only overestimate.
+#[derive(Debug)]
+struct CountMinSketch {
+    counters: Vec<u8>,
+    width: u64
+}
+
+impl CountMinSketch {
+    fn new(width: usize) -> Self {
+        CountMinSketch {
+            counters: vec![0; width * DEPTH as usize],
+            width: width as u64
+        }
+    }
+
+    // The index of the hash's counter in the given row
+    fn index(&self, hash: u64, row: u64) -> usize {
+        (row * self.width + probe(hash, row) % self.width) as usize
+    }
+
+    fn increment(&mut self, hash: u64) {
+        for row in 0..DEPTH {
+            let index = self.index(hash, row);
+            if self.counters[index] < MAX_COUNT {
+                self.counters[index] += 1;
+            }
+        }
+    }
+
+    fn estimate(&self, hash: u64) -> u8 {
+        (0..DEPTH).map(|row| self.counters[self.index(hash, row)]).min().unwrap_or(0)
+    }
+
+    fn halve(&mut self) {
+        for counter in &mut self.counters {
+            *counter /= 2;
+        }
+    }
+}
+
+// A bloom filter of the keys seen once since the last reset, so keys
+// that are only ever seen once never reach the sketch
+#[derive(Debug)]
+struct Doorkeeper {
+    bits: Vec<u64>,
+    len: u64
+}
+
+impl Doorkeeper {
+    fn new(len: usize) -> Self {
+        Doorkeeper {
+            bits: vec![0; (len + 63) / 64],
+            len: len as u64
+        }
+    }
+
+    fn contains(&self, hash: u64) -> bool {
+        (0..2).all(|probe_index| {
+            let bit = probe(hash, probe_index) % self.len;
+            self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0
+        })
+    }
+
+    // Add the hash, returning whether it was already present
+    fn insert(&mut self, hash: u64) -> bool {
+        let present = self.contains(hash);
+        for probe_index in 0..2 {
+            let bit = probe(hash, probe_index) % self.len;
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+        present
+    }
+
+    fn clear(&mut self) {
+        for word in &mut self.bits {
+            *word = 0;
+        }
+    }
+}
+
+// Derive the i-th probe of a key from its hash by double hashing
+fn probe(hash: u64, i: u64) -> u64 {
+    let h1 = hash & 0xffff_ffff;
+    let h2 = (hash >> 32) | 1;
+    h1.wrapping_add(i.wrapping_mul(h2))
+}
+
+// The TinyLFU admission filter, see LFUCache::set_admission_filter.
+//
+// Every lookup and insertion records the key. When inserting a new
+// key means evicting another, the new key is only admitted if it has
+// been seen more often than the key it would evict. Otherwise one-hit
+// wonders would keep evicting each other's useful predecessors.
+//
+// Frequencies are kept in a count-min sketch behind a doorkeeper bloom
+// filter. After `sample_size` records all counts are halved and the
+// doorkeeper is cleared, so the filter follows changes in popularity.
+#[derive(Debug)]
+pub struct TinyLfu {
+    sketch: CountMinSketch,
+    doorkeeper: Doorkeeper,
+    hash_builder: RandomState,
+    sample_size: usize,
+    additions: usize
+}
+
+impl TinyLfu {
+    // Create a filter sized for a cache of the given capacity, reset
+    // every 10 times the capacity records
+    pub fn new(capacity: usize) -> Self {
+        TinyLfu::with_sample_size(capacity, cmp::max(capacity, 1).saturating_mul(10))
+    }
+
+    pub fn with_sample_size(capacity: usize, sample_size: usize) -> Self {
+        let width = cmp::max(capacity, 16).next_power_of_two();
+        TinyLfu {
+            sketch: CountMinSketch::new(width),
+            doorkeeper: Doorkeeper::new(width * 8),
+            hash_builder: RandomState::new(),
+            sample_size: cmp::max(sample_size, 1),
+            additions: 0
+        }
+    }
+
+    pub fn sample_size(&self) -> usize {
+        self.sample_size
+    }
+
+    fn hash<K: Hash + ?Sized>(&self, key: &K) -> u64 {
+        let mut hasher = self.hash_builder.build_hasher();
+        key.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // Record a use of the given key
+    pub fn record<K: Hash + ?Sized>(&mut self, key: &K) {
+        let hash = self.hash(key);
+        if self.doorkeeper.insert(hash) {
+            self.sketch.increment(hash);
+        }
+        self.additions += 1;
+        if self.additions >= self.sample_size {
+            self.reset();
+        }
+    }
+
+    // The estimated number of uses of the given key since the filter
+    // was last reset, counting down older uses
+    pub fn estimate<K: Hash + ?Sized>(&self, key: &K) -> usize {
+        let hash = self.hash(key);
+        let seen = if self.doorkeeper.contains(hash) { 1 } else { 0 };
+        self.sketch.estimate(hash) as usize + seen
+    }
+
+    // Halve every count and forget the keys only seen once
+    pub fn reset(&mut self) {
+        self.sketch.halve();
+        self.doorkeeper.clear();
+        self.additions = 0;
+    }
+
+    // Whether a new key should be admitted at the expense of the
+    // given victim
+    pub fn admit<K: Hash + ?Sized>(&self, candidate: &K, victim: &K) -> bool {
+        self.estimate(candidate) > self.estimate(victim)
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::hash_map::RandomState;
+use std::error::Error;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::time::Duration;
+
+use {Clock, EvictionListener, EvictionPolicy, LFUCache, LfuPolicy, TieBreaking, TinyLfu, Weigher};
+
+// Why LFUCacheBuilder::build rejected its configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BuildError {
+    // No capacity was given
+    MissingCapacity,
+    // The capacity was 0, so the cache could never hold anything
+    ZeroCapacity,
+    // The default time to live was 0, so every entry would expire as
+    // soon as it was inserted
+    ZeroTimeToLive,
+    // The aging interval was 0
+    ZeroAgingInterval,
+    // Both a policy and a tie-breaking rule were given. Tie-breaking
+    // configures the default policy; pass it to a custom policy's own
+    // constructor instead.
+    TieBreakingWithPolicy
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match *self {
+            BuildError::MissingCapacity => "no capacity was set",
+            BuildError::ZeroCapacity => "the capacity must be greater than 0",
+            BuildError::ZeroTimeToLive => "the default time to live must be greater than 0",
+            BuildError::ZeroAgingInterval => "the aging interval must be greater than 0",
+            BuildError::TieBreakingWithPolicy =>
+                "tie-breaking only applies to the default policy, not to a custom one"
+        };
+        write!(f, "invalid cache configuration: {}", message)
+    }
+}
+
+impl Error for BuildError {}
+
+// Configures and creates an LFUCache, see LFUCache::builder. Only the
+// capacity is required; everything else defaults to what
+// LFUCache::new gives.
+pub struct LFUCacheBuilder<K, V, S = RandomState> {
+    capacity: Option<usize>,
+    initial_capacity: usize,
+    hash_builder: S,
+    policy: Option<Box<dyn EvictionPolicy + Send>>,
+    tie_breaking: Option<TieBreaking>,
+    default_ttl: Option<Duration>,
+    clock: Option<Box<dyn Clock + Send>>,
+    weigher: Option<Box<dyn Weigher<K, V> + Send>>,
+    listener: Option<Box<dyn EvictionListener<K, V> + Send>>,
+    aging_interval: Option<usize>,
+    admission: Option<TinyLfu>
+}
+
+impl<K, V> LFUCacheBuilder<K, V>
+where K: Hash + Eq {
+    pub fn new() -> Self {
+        LFUCacheBuilder {
+            capacity: None,
+            initial_capacity: 0,
+            hash_builder: RandomState::new(),
+            policy: None,
+            tie_breaking: None,
+            default_ttl: None,
+            clock: None,
+            weigher: None,
+            listener: None,
+            aging_interval: None,
+            admission: None
+        }
+    }
+}
+
+impl<K, V, S> LFUCacheBuilder<K, V, S>
+where K: Hash + Eq, S: BuildHasher {
+    // The maximum total weight of the cache; the maximum number of
+    // keys without a weigher
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    // The number of keys to allocate room for up front
+    pub fn initial_capacity(mut self, initial_capacity: usize) -> Self {
+        self.initial_capacity = initial_capacity;
+        self
+    }
+
+    // Hash keys with the given hasher instead of RandomState
+    pub fn hasher<T>(self, hash_builder: T) -> LFUCacheBuilder<K, V, T>
+    where T: BuildHasher {
+        LFUCacheBuilder {
+            capacity: self.capacity,
+            initial_capacity: self.initial_capacity,
+            hash_builder,
+            policy: self.policy,
+            tie_breaking: self.tie_breaking,
+            default_ttl: self.default_ttl,
+            clock: self.clock,
+            weigher: self.weigher,
+            listener: self.listener,
+            aging_interval: self.aging_interval,
+            admission: self.admission
+        }
+    }
+
+    pub fn policy<P>(mut self, policy: P) -> Self
+    where P: EvictionPolicy + Send + 'static {
+        self.policy = Some(Box::new(policy));
+        self
+    }
+
+    // How the default LfuPolicy breaks ties between equally frequently
+    // used keys. TieBreaking::FirstInFirstOut makes each use walk the
+    // frequency list the key moves to, so `get` is no longer O(1).
+    pub fn tie_breaking(mut self, tie_breaking: TieBreaking) -> Self {
+        self.tie_breaking = Some(tie_breaking);
+        self
+    }
+
+    pub fn default_ttl(mut self, ttl: Duration) -> Self {
+        self.default_ttl = Some(ttl);
+        self
+    }
+
+    pub fn clock<C>(mut self, clock: C) -> Self
+    where C: Clock + Send + 'static {
+        self.clock = Some(Box::new(clock));
+        self
+    }
+
+    pub fn weigher<W>(mut self, weigher: W) -> Self
+    where W: Weigher<K, V> + Send + 'static {
+        self.weigher = Some(Box::new(weigher));
+        self
+    }
+
+    pub fn eviction_listener<L>(mut self, listener: L) -> Self
+    where L: EvictionListener<K, V> + Send + 'static {
+        self.listener = Some(Box::new(listener));
+        self
+    }
+
+    // Halve all frequencies every `interval` uses and insertions, see
+    // LFUCache::set_aging_interval
+    pub fn aging_interval(mut self, interval: usize) -> Self {
+        self.aging_interval = Some(interval);
+        self
+    }
+
+    pub fn admission_filter(mut self, filter: TinyLfu) -> Self {
+        self.admission = Some(filter);
+        self
+    }
+
+    pub fn build(self) -> Result<LFUCache<K, V, S>, BuildError> {
+        let capacity = self.capacity.ok_or(BuildError::MissingCapacity)?;
+        if capacity == 0 {
+            return Err(BuildError::ZeroCapacity);
+        }
+        if self.default_ttl == Some(Duration::from_secs(0)) {
+            return Err(BuildError::ZeroTimeToLive);
+        }
+        if self.aging_interval == Some(0) {
+            return Err(BuildError::ZeroAgingInterval);
+        }
+        let policy = match (self.policy, self.tie_breaking) {
+            (Some(_), Some(_)) => return Err(BuildError::TieBreakingWithPolicy),
+            (Some(policy), None) => policy,
+            (None, tie_breaking) =>
+                Box::new(LfuPolicy::with_tie_breaking(tie_breaking.unwrap_or_default()))
+        };
+
+        let mut cache =
+            LFUCache::with_capacity_and_hasher(capacity, self.initial_capacity, self.hash_builder);
+        cache.policy = policy;
+        cache.default_ttl = self.default_ttl;
+        if let Some(clock) = self.clock {
+            cache.clock = clock;
+        }
+        cache.weigher = self.weigher;
+        cache.listener = self.listener;
+        cache.aging_interval = self.aging_interval;
+        cache.admission = self.admission;
+        Ok(cache)
+    }
+}
+
+impl<K, V> Default for LFUCacheBuilder<K, V>
+where K: Hash + Eq {
+    fn default() -> Self {
+        LFUCacheBuilder::new()
+    }
+}
@@ -0,0 +1,51 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// The source of time an LFUCache uses to expire entries, see
+// LFUCache::set_clock.
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+// The default clock, reading the system's monotonic clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+// A clock that only moves when it is told to, for testing expiry
+// without sleeping. Clones share the same time, so a test can keep
+// one clone and hand another to the cache.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Arc<Mutex<Instant>>
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        ManualClock {
+            now: Arc::new(Mutex::new(Instant::now()))
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.now.lock().expect("a thread panicked while holding the clock lock");
+        *now += duration;
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        ManualClock::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().expect("a thread panicked while holding the clock lock")
+    }
+}
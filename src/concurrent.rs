@@ -0,0 +1,84 @@
+use std::borrow::Borrow;
+use std::hash::Hash;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use {EvictionListener, LFUCache};
+
+// An LFU cache that can be shared between threads, e.g. through an
+// Arc. Every operation takes `&self`; the underlying LFUCache is
+// guarded by a lock, so eviction behaves exactly like
+// LFUCache::insert.
+#[derive(Debug)]
+pub struct ConcurrentLFUCache<K, V>
+where K: Hash + Eq {
+    cache: Mutex<LFUCache<K, V>>
+}
+
+impl<K, V> ConcurrentLFUCache<K, V>
+where K: Hash + Eq {
+    pub fn new(max_size: usize) -> Self {
+        ConcurrentLFUCache {
+            cache: Mutex::new(LFUCache::new(max_size))
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LFUCache<K, V>> {
+        self.cache.lock().expect("a thread panicked while holding the cache lock")
+    }
+
+    // Register a listener that is called with every entry that leaves
+    // the cache. The listener runs while the cache lock is held.
+    pub fn set_eviction_listener<L>(&self, listener: L)
+    where L: EvictionListener<K, V> + Send + 'static {
+        self.lock().set_eviction_listener(listener)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.lock().capacity()
+    }
+
+    // Change the maximum number of keys the cache holds, returning
+    // the keys evicted to fit, see LFUCache::set_capacity.
+    pub fn set_capacity(&self, max_size: usize) -> Vec<(K, V)> {
+        self.lock().set_capacity(max_size)
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    // Get a clone of the value associated with the given key. Like
+    // LFUCache::get, this increments the key's frequency.
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized, V: Clone {
+        self.lock().get(key).cloned()
+    }
+
+    // Remove the value associated with the given key.
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        self.lock().remove(key)
+    }
+
+    // Insert the value associated with the given key, evicting the
+    // least frequently used key if the cache is full.
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        self.lock().insert(key, value)
+    }
+
+    // Insert the value associated with the given key, expiring after
+    // the given time to live, see LFUCache::insert_with_ttl.
+    pub fn insert_with_ttl(&self, key: K, value: V, ttl: Duration) -> Option<V> {
+        self.lock().insert_with_ttl(key, value, ttl)
+    }
+
+    // Remove every expired entry, see LFUCache::purge_expired.
+    pub fn purge_expired(&self) -> Vec<(K, V)> {
+        self.lock().purge_expired()
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use {EvictionReason, LFUCache};
+
+// A view into a single key of an LFUCache, returned by
+// LFUCache::entry. The lookup that created an occupied entry has
+// already counted as a use of the key, so none of these methods
+// change its frequency again.
+pub enum Entry<'a, K, V, S = RandomState>
+where K: Hash + Eq + 'a, V: 'a, S: 'a {
+    Occupied(OccupiedEntry<'a, K, V, S>),
+    Vacant(VacantEntry<'a, K, V, S>)
+}
+
+// An entry for a key that is in the cache
+pub struct OccupiedEntry<'a, K, V, S = RandomState>
+where K: Hash + Eq + 'a, V: 'a, S: 'a {
+    cache: &'a mut LFUCache<K, V, S>,
+    node: usize
+}
+
+// An entry for a key that is not in the cache
+pub struct VacantEntry<'a, K, V, S = RandomState>
+where K: Hash + Eq + 'a, V: 'a, S: 'a {
+    cache: &'a mut LFUCache<K, V, S>,
+    key: K
+}
+
+impl<'a, K, V, S> Entry<'a, K, V, S>
+where K: Hash + Eq, S: BuildHasher {
+    pub fn key(&self) -> &K {
+        match *self {
+            Entry::Occupied(ref entry) => entry.key(),
+            Entry::Vacant(ref entry) => entry.key()
+        }
+    }
+
+    // Insert the given value if the key is vacant, evicting the least
+    // frequently used key if the cache is full. Returns a mutable
+    // reference to the value in the cache.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default)
+        }
+    }
+
+    // Like `or_insert`, but only computes the value if the key is
+    // vacant.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default())
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where V: Default {
+        self.or_insert_with(V::default)
+    }
+
+    // Modify the value in place if the key is occupied
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        match self {
+            Entry::Occupied(mut entry) => {
+                f(entry.get_mut());
+                Entry::Occupied(entry)
+            }
+            Entry::Vacant(entry) => Entry::Vacant(entry)
+        }
+    }
+}
+
+impl<'a, K, V, S> OccupiedEntry<'a, K, V, S>
+where K: Hash + Eq, S: BuildHasher {
+    pub(crate) fn new(cache: &'a mut LFUCache<K, V, S>, node: usize) -> Self {
+        OccupiedEntry { cache, node }
+    }
+
+    pub fn key(&self) -> &K {
+        &self.cache.nodes[self.node].key
+    }
+
+    pub fn get(&self) -> &V {
+        &self.cache.nodes[self.node].value
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.cache.nodes[self.node].value
+    }
+
+    // Convert the entry into a mutable reference to its value that
+    // lives as long as the borrow of the cache
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.cache.nodes[self.node].value
+    }
+
+    // Replace the value, returning the old one. Like
+    // LFUCache::insert, this resets the key's expiry time to the
+    // default time to live. If the new value is heavier, other keys
+    // are evicted to make room, but never this one.
+    pub fn insert(&mut self, value: V) -> V {
+        let weight = self.cache.weigh(&self.cache.nodes[self.node].key, &value);
+        let old_value = self.cache.replace_value(self.node, value, weight);
+        let expires_at = self.cache.expiry_after(self.cache.default_ttl);
+        self.cache.set_expiry(self.node, expires_at);
+        self.cache.evict_to_fit(0, Some(self.node));
+        old_value
+    }
+
+    // Remove the key from the cache, returning its value
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    pub fn remove_entry(self) -> (K, V) {
+        self.cache.remove_node(self.node, EvictionReason::Removed)
+    }
+}
+
+impl<'a, K, V, S> VacantEntry<'a, K, V, S>
+where K: Hash + Eq, S: BuildHasher {
+    pub(crate) fn new(cache: &'a mut LFUCache<K, V, S>, key: K) -> Self {
+        VacantEntry { cache, key }
+    }
+
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    // Insert the key with a frequency of 1, evicting least frequently
+    // used keys until it fits. Since a reference to the value has to
+    // be returned, a value heavier than the whole capacity is kept
+    // anyway, alone, until the next insertion evicts it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        let weight = self.cache.weigh(&self.key, &value);
+        let expires_at = self.cache.expiry_after(self.cache.default_ttl);
+        self.cache.evict_to_fit(weight, None);
+        let node = self.cache.insert_new(self.key, value, weight);
+        self.cache.set_expiry(node, expires_at);
+        &mut self.cache.nodes[node].value
+    }
+}
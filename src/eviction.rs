@@ -0,0 +1,43 @@
+// Why an entry left the cache, passed to the eviction listener
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EvictionReason {
+    // Evicted as the least frequently used entry to make room for a
+    // new one
+    Capacity,
+    // Removed explicitly, e.g. by LFUCache::remove
+    Removed,
+    // The value was overwritten by inserting the same key again. The
+    // key itself stays in the cache.
+    Replaced,
+    // The entry's time to live ran out
+    Expired,
+    // The admission filter preferred the entry this one would have
+    // evicted, so it was never kept
+    Rejected
+}
+
+// The outcome of LFUCache::insert_with_eviction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InsertResult<K, V> {
+    // The key's old value, if it was already in the cache
+    pub replaced: Option<V>,
+    // The entries evicted to make room, in eviction order. This
+    // includes the inserted entry itself if it was too heavy to keep
+    // or rejected by the admission filter.
+    pub evicted: Vec<(K, V)>
+}
+
+// Receives every entry that leaves an LFUCache, see
+// LFUCache::set_eviction_listener. The listener is called before the
+// entry is dropped or handed back to the caller, with the key's
+// frequency at that point.
+pub trait EvictionListener<K, V> {
+    fn on_evict(&mut self, key: &K, value: &V, frequency: usize, reason: EvictionReason);
+}
+
+impl<K, V, F> EvictionListener<K, V> for F
+where F: FnMut(&K, &V, usize, EvictionReason) {
+    fn on_evict(&mut self, key: &K, value: &V, frequency: usize, reason: EvictionReason) {
+        self(key, value, frequency, reason)
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+use LFUCache;
+
+// A broken internal invariant found by LFUCache::validate. This is
+// always a bug, either in the cache or in a custom eviction policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvariantError {
+    message: String
+}
+
+impl InvariantError {
+    pub fn new<M: Into<String>>(message: M) -> Self {
+        InvariantError { message: message.into() }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for InvariantError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cache invariant violated: {}", self.message)
+    }
+}
+
+impl Error for InvariantError {}
+
+impl<K, V, S> LFUCache<K, V, S>
+where K: Hash + Eq, S: BuildHasher {
+    // Check the cache's internal structure, walking every entry. This
+    // is O(n) and only meant for debugging and tests. Checks that
+    //   - the map and the node slab hold the same keys,
+    //   - the eviction policy's own structure is sound,
+    //   - the policy's order holds every node exactly once, with `prev`
+    //     mirroring `next`,
+    //   - the total weight and the expiry index match the nodes.
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        if self.cache.len() != self.nodes.len() {
+            return Err(InvariantError::new(format!(
+                "the map holds {} keys but there are {} nodes", self.cache.len(), self.nodes.len())));
+        }
+        for (key, &index) in &self.cache {
+            match self.nodes.get(index) {
+                Some(node) if *node.key == **key => {}
+                Some(_) => return Err(InvariantError::new(format!(
+                    "the map points at node {}, which holds another key", index))),
+                None => return Err(InvariantError::new(format!(
+                    "the map points at missing node {}", index)))
+            }
+        }
+
+        self.policy.validate()?;
+        let mut seen = HashSet::with_capacity(self.nodes.len());
+        let mut prev = None;
+        let mut current = self.first_node();
+        while let Some(index) = current {
+            if !self.nodes.contains(index) {
+                return Err(InvariantError::new(format!(
+                    "the eviction order contains missing node {}", index)));
+            }
+            if !seen.insert(index) {
+                return Err(InvariantError::new(format!(
+                    "the eviction order contains node {} twice", index)));
+            }
+            if self.policy.prev(index) != prev {
+                return Err(InvariantError::new(format!(
+                    "node {} comes after {:?}, but the policy puts {:?} before it",
+                    index, prev, self.policy.prev(index))));
+            }
+            prev = current;
+            current = self.node_after(index);
+        }
+        if self.last_node() != prev {
+            return Err(InvariantError::new(format!(
+                "the eviction order ends at {:?} but the policy's last node is {:?}",
+                prev, self.last_node())));
+        }
+        if seen.len() != self.nodes.len() {
+            return Err(InvariantError::new(format!(
+                "the eviction order holds {} of the {} nodes", seen.len(), self.nodes.len())));
+        }
+
+        let weight: usize = self.nodes.iter().map(|(_, node)| node.weight).sum();
+        if weight != self.weight {
+            return Err(InvariantError::new(format!(
+                "the nodes weigh {} but the cache's weight is {}", weight, self.weight)));
+        }
+        for &(expires_at, index) in &self.expirations {
+            if self.nodes.get(index).and_then(|node| node.expires_at) != Some(expires_at) {
+                return Err(InvariantError::new(format!(
+                    "the expiry index holds a stale expiry time for node {}", index)));
+            }
+        }
+        let expiring = self.nodes.iter().filter(|&(_, node)| node.expires_at.is_some()).count();
+        if expiring != self.expirations.len() {
+            return Err(InvariantError::new(format!(
+                "{} nodes expire but the expiry index holds {}", expiring, self.expirations.len())));
+        }
+        if let Some((index, _)) = self.nodes.iter().find(|&(_, node)| node.frequency == 0) {
+            return Err(InvariantError::new(format!("node {} has a frequency of 0", index)));
+        }
+        Ok(())
+    }
+
+    // Like `validate`, but panics if an invariant is broken
+    pub fn check_invariants(&self) {
+        if let Err(error) = self.validate() {
+            panic!("{}", error);
+        }
+    }
+
+    // Check every invariant after each change to the cache when the
+    // debug-invariants feature is enabled, and do nothing otherwise
+    #[inline]
+    pub(crate) fn debug_check_invariants(&self) {
+        #[cfg(feature = "debug-invariants")]
+        self.check_invariants();
+    }
+}
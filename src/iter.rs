@@ -0,0 +1,250 @@
+use std::hash::Hash;
+use std::vec;
+
+use nodes::Slab;
+use {CacheNode, EvictionPolicy, LFUCache};
+
+// Iterator over the entries of an LFUCache in eviction order, from
+// the least to the most frequently used key, see LFUCache::iter.
+// Yields each key and value along with its frequency.
+pub struct Iter<'a, K, V>
+where K: Hash + Eq + 'a, V: 'a {
+    nodes: &'a Slab<CacheNode<K, V>>,
+    policy: &'a (dyn EvictionPolicy + Send),
+    front: Option<usize>,
+    back: Option<usize>,
+    remaining: usize
+}
+
+impl<'a, K, V> Iter<'a, K, V>
+where K: Hash + Eq {
+    pub(crate) fn new<S>(cache: &'a LFUCache<K, V, S>) -> Self {
+        Iter {
+            nodes: &cache.nodes,
+            policy: &*cache.policy,
+            front: cache.first_node(),
+            back: cache.last_node(),
+            remaining: cache.cache.len()
+        }
+    }
+
+    fn entry(&self, node: usize) -> (&'a K, &'a V, usize) {
+        let node = &self.nodes[node];
+        (&*node.key, &node.value, node.frequency)
+    }
+
+    // Collect the node index and frequency of every remaining entry
+    fn map_nodes(mut self) -> Vec<(usize, usize)> {
+        let mut order = Vec::with_capacity(self.remaining);
+        while self.remaining > 0 {
+            let node = self.front.expect("remaining nodes are linked");
+            self.remaining -= 1;
+            self.front = self.policy.next(node);
+            order.push((node, self.nodes[node].frequency));
+        }
+        order
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V>
+where K: Hash + Eq {
+    type Item = (&'a K, &'a V, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.front?;
+        self.remaining -= 1;
+        self.front = self.policy.next(node);
+        Some(self.entry(node))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V>
+where K: Hash + Eq {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.back?;
+        self.remaining -= 1;
+        self.back = self.policy.prev(node);
+        Some(self.entry(node))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V>
+where K: Hash + Eq {}
+
+// Mutable iterator over the entries of an LFUCache in eviction order,
+// see LFUCache::iter_mut. The order is collected up front so that
+// every node can be handed out exactly once.
+pub struct IterMut<'a, K: 'a, V: 'a> {
+    order: vec::IntoIter<(usize, usize)>,
+    nodes: Vec<Option<&'a mut CacheNode<K, V>>>
+}
+
+impl<'a, K, V> IterMut<'a, K, V>
+where K: Hash + Eq {
+    pub(crate) fn new<S>(cache: &'a mut LFUCache<K, V, S>) -> Self {
+        let order: Vec<(usize, usize)> = Iter::new(cache).map_nodes();
+        let mut nodes: Vec<Option<&'a mut CacheNode<K, V>>> =
+            (0..cache.nodes.capacity()).map(|_| None).collect();
+        for (index, node) in cache.nodes.iter_mut() {
+            nodes[index] = Some(node);
+        }
+        IterMut { order: order.into_iter(), nodes }
+    }
+
+    fn entry(&mut self, (node, frequency): (usize, usize)) -> (&'a K, &'a mut V, usize) {
+        let node = self.nodes[node].take().expect("each node is yielded once");
+        (&*node.key, &mut node.value, frequency)
+    }
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V>
+where K: Hash + Eq {
+    type Item = (&'a K, &'a mut V, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.order.next()?;
+        Some(self.entry(next))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.order.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for IterMut<'a, K, V>
+where K: Hash + Eq {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let next = self.order.next_back()?;
+        Some(self.entry(next))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for IterMut<'a, K, V>
+where K: Hash + Eq {}
+
+// Owning iterator over the entries of an LFUCache in eviction order,
+// see LFUCache::into_iter.
+pub struct IntoIter<K, V> {
+    order: vec::IntoIter<(usize, usize)>,
+    nodes: Vec<Option<CacheNode<K, V>>>
+}
+
+impl<K, V> IntoIter<K, V>
+where K: Hash + Eq {
+    pub(crate) fn new<S>(cache: LFUCache<K, V, S>) -> Self {
+        let order = Iter::new(&cache).map_nodes();
+        IntoIter { order: order.into_iter(), nodes: cache.nodes.into_slots() }
+    }
+
+    fn entry(&mut self, (node, frequency): (usize, usize)) -> (K, V, usize) {
+        let node = self.nodes[node].take().expect("each node is yielded once");
+        (node.key.into_inner(), node.value, frequency)
+    }
+}
+
+impl<K, V> Iterator for IntoIter<K, V>
+where K: Hash + Eq {
+    type Item = (K, V, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.order.next()?;
+        Some(self.entry(next))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.order.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IntoIter<K, V>
+where K: Hash + Eq {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let next = self.order.next_back()?;
+        Some(self.entry(next))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V>
+where K: Hash + Eq {}
+
+// Iterator over the keys of an LFUCache in eviction order
+pub struct Keys<'a, K, V>
+where K: Hash + Eq + 'a, V: 'a {
+    inner: Iter<'a, K, V>
+}
+
+impl<'a, K, V> Keys<'a, K, V>
+where K: Hash + Eq {
+    pub(crate) fn new(inner: Iter<'a, K, V>) -> Self {
+        Keys { inner }
+    }
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V>
+where K: Hash + Eq {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<&'a K> {
+        self.inner.next().map(|(key, _, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Keys<'a, K, V>
+where K: Hash + Eq {
+    fn next_back(&mut self) -> Option<&'a K> {
+        self.inner.next_back().map(|(key, _, _)| key)
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Keys<'a, K, V>
+where K: Hash + Eq {}
+
+// Iterator over the values of an LFUCache in eviction order
+pub struct Values<'a, K, V>
+where K: Hash + Eq + 'a, V: 'a {
+    inner: Iter<'a, K, V>
+}
+
+impl<'a, K, V> Values<'a, K, V>
+where K: Hash + Eq {
+    pub(crate) fn new(inner: Iter<'a, K, V>) -> Self {
+        Values { inner }
+    }
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V>
+where K: Hash + Eq {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<&'a V> {
+        self.inner.next().map(|(_, value, _)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Values<'a, K, V>
+where K: Hash + Eq {
+    fn next_back(&mut self) -> Option<&'a V> {
+        self.inner.next_back().map(|(_, value, _)| value)
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Values<'a, K, V>
+where K: Hash + Eq {}
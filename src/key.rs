@@ -0,0 +1,99 @@
+use std::borrow::Borrow;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::Arc;
+
+// A key held once and shared between the cache's map and its node,
+// so keys never need to be cloned. The map's reference is always
+// dropped before the node's, which leaves the node the only owner
+// when an entry is removed.
+//
+// Arc rather than Rc keeps the cache Send, at the cost of requiring
+// the key to be Sync as well.
+pub(crate) struct SharedKey<K>(Arc<K>);
+
+impl<K> SharedKey<K> {
+    pub(crate) fn new(key: K) -> Self {
+        SharedKey(Arc::new(key))
+    }
+
+    // Another reference to the same key
+    pub(crate) fn share(&self) -> Self {
+        SharedKey(Arc::clone(&self.0))
+    }
+
+    // Take the key back out, once every other reference to it has
+    // been dropped
+    pub(crate) fn into_inner(self) -> K {
+        Arc::try_unwrap(self.0).ok().expect("the key is no longer shared")
+    }
+}
+
+impl<K> Deref for SharedKey<K> {
+    type Target = K;
+
+    fn deref(&self) -> &K {
+        &self.0
+    }
+}
+
+impl<K: fmt::Debug> fmt::Debug for SharedKey<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (*self.0).fmt(f)
+    }
+}
+
+impl<K: Hash> Hash for SharedKey<K> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (*self.0).hash(state)
+    }
+}
+
+impl<K: PartialEq> PartialEq for SharedKey<K> {
+    fn eq(&self, other: &Self) -> bool {
+        *self.0 == *other.0
+    }
+}
+
+impl<K: Eq> Eq for SharedKey<K> {}
+
+// A borrowed form of a key to look SharedKeys up with. HashMap needs
+// SharedKey<K>: Borrow<Q> for every Q that K borrows as, but that
+// impl would overlap with the blanket Borrow<T> for T, so lookups go
+// through this wrapper instead. repr(transparent), which the cast in
+// `new` relies on, needs Rust 1.28.
+#[repr(transparent)]
+pub(crate) struct Lookup<Q: ?Sized>(Q);
+
+impl<Q: ?Sized> Lookup<Q> {
+    pub(crate) fn new(key: &Q) -> &Self {
+        // Lookup is a transparent wrapper, so &Q and &Lookup<Q> have
+        // the same layout
+        unsafe { &*(key as *const Q as *const Lookup<Q>) }
+    }
+}
+
+impl<Q: Hash + ?Sized> Hash for Lookup<Q> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl<Q: PartialEq + ?Sized> PartialEq for Lookup<Q> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<Q: Eq + ?Sized> Eq for Lookup<Q> {}
+
+// Borrow requires the borrowed form to hash and compare like the key
+// itself, which holds because both delegate to K and Q, and K: Borrow<Q>
+// already promises the same of them
+impl<K, Q> Borrow<Lookup<Q>> for SharedKey<K>
+where K: Borrow<Q>, Q: ?Sized {
+    fn borrow(&self) -> &Lookup<Q> {
+        Lookup::new((*self.0).borrow())
+    }
+}
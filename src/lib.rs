@@ -1,138 +1,333 @@
-use std::collections::HashMap;
-use std::hash::Hash;
-use std::rc::{Rc, Weak};
-use std::cell::RefCell;
-use std::fmt::Display;
+use std::cmp;
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeSet, HashMap};
+use std::hash::{BuildHasher, Hash};
+use std::fmt::{self, Display};
+use std::mem;
+use std::time::{Duration, Instant};
 
 pub mod nodes;
-use nodes::{HasHead, Node};
+use nodes::Slab;
 
-// A single node in the cache
+mod key;
+use key::{Lookup, SharedKey};
+
+pub mod concurrent;
+pub use concurrent::ConcurrentLFUCache;
+
+pub mod sharded;
+pub use sharded::{ShardedLFUCache, ShardStats};
+
+pub mod entry;
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
+
+pub mod iter;
+pub use iter::{IntoIter, Iter, IterMut, Keys, Values};
+
+pub mod eviction;
+pub use eviction::{EvictionListener, EvictionReason, InsertResult};
+
+pub mod weigher;
+pub use weigher::Weigher;
+
+pub mod clock;
+pub use clock::{Clock, ManualClock, SystemClock};
+
+pub mod admission;
+pub use admission::TinyLfu;
+
+pub mod policy;
+pub use policy::{EvictionPolicy, FifoPolicy, LfuDaPolicy, LfuPolicy, LruPolicy, TieBreaking};
+
+pub mod builder;
+pub use builder::{BuildError, LFUCacheBuilder};
+
+pub mod invariants;
+pub use invariants::InvariantError;
+
+// Times to live longer than this, about 100 years, are treated as
+// never expiring
+const MAX_TTL_SECS: u64 = 100 * 365 * 24 * 60 * 60;
+
+// A single entry in the cache. Nodes live in the cache's node slab;
+// their index is the handle the eviction policy knows them by.
 #[derive(Debug)]
-pub struct CacheNode<K, V>
-where K: Hash + Eq + Clone {
-    parent: Rc<RefCell<FrequencyList<K, V>>>,
-    key: K,
-    next: Option<Rc<RefCell<CacheNode<K, V>>>>,
-    prev: Option<Weak<RefCell<CacheNode<K, V>>>>
+struct CacheNode<K, V> {
+    key: SharedKey<K>,
+    value: V,
+    weight: usize,
+    frequency: usize,
+    expires_at: Option<Instant>
+}
+
+// This is the main struct and the entrypoint to the cache. Nodes are
+// stored in a slab and the hashmap maps each key to the slab index of
+// its node. Each key is held once, shared between the hashmap and its
+// node, so keys don't need to be Clone. Which node to evict is up to
+// the eviction policy, by default LfuPolicy.
+//
+// Capacity is measured in weight. Without a weigher every entry weighs
+// 1, so the capacity is simply the maximum number of keys.
+//
+// Entries with a time to live are also indexed by their expiry time,
+// so expired entries can be found without walking the whole cache.
+//
+// With aging enabled, every `aging_interval` uses and insertions all
+// frequencies are halved, so keys that were hot long ago can be
+// displaced by keys that are hot now.
+//
+// With an admission filter, a new key that would evict another is
+// only inserted if the filter has seen it more often than its victim.
+//
+// Like HashMap, keys are hashed with RandomState unless another
+// BuildHasher is given.
+pub struct LFUCache<K, V, S = RandomState>
+where K: Hash + Eq {
+    policy: Box<dyn EvictionPolicy + Send>,
+    nodes: Slab<CacheNode<K, V>>,
+    cache: HashMap<SharedKey<K>, usize, S>,
+    max_size: usize,
+    weight: usize,
+    weigher: Option<Box<dyn Weigher<K, V> + Send>>,
+    listener: Option<Box<dyn EvictionListener<K, V> + Send>>,
+    default_ttl: Option<Duration>,
+    expirations: BTreeSet<(Instant, usize)>,
+    clock: Box<dyn Clock + Send>,
+    aging_interval: Option<usize>,
+    operations: usize,
+    admission: Option<TinyLfu>
 }
 
-impl<K, V> CacheNode<K, V>
-where K: Hash + Eq + Clone {
-    // Get the actual value associated with this cache node. This is
-    // used exclusively for testing and display purposes.
-    pub fn get_associated_data<'a>(&self, cache: &'a LFUCache<K, V>) -> &'a V {
-        &cache.cache.get(&self.key).unwrap().0
+impl<K, V> LFUCache<K, V>
+where K: Hash + Eq {
+    pub fn new(max_size: usize) -> Self {
+        LFUCache::with_hasher(max_size, RandomState::new())
+    }
+
+    // Configure a cache step by step, e.g.
+    // `LFUCache::builder().capacity(10).default_ttl(ttl).build()`
+    pub fn builder() -> LFUCacheBuilder<K, V> {
+        LFUCacheBuilder::new()
+    }
+
+    // Create a cache that evicts keys according to the given policy
+    // instead of evicting the least frequently used one
+    pub fn with_policy<P>(max_size: usize, policy: P) -> Self
+    where P: EvictionPolicy + Send + 'static {
+        let mut cache = LFUCache::new(max_size);
+        cache.policy = Box::new(policy);
+        cache
+    }
+
+    // Create a cache whose entries are weighed by the given weigher,
+    // evicting least frequently used keys whenever their total weight
+    // would exceed `max_weight`. Entries are weighed when they are
+    // inserted; changing a value through `get_mut` and friends doesn't
+    // re-weigh it.
+    pub fn with_weigher<W>(max_weight: usize, weigher: W) -> Self
+    where W: Weigher<K, V> + Send + 'static {
+        let mut cache = LFUCache::new(max_weight);
+        cache.weigher = Some(Box::new(weigher));
+        cache
     }
 }
 
-impl<K, V> nodes::Node for CacheNode<K, V>
-where K: Hash + Eq + Clone {
-    fn get_next(&self) -> Option<Rc<RefCell<CacheNode<K, V>>>> {
-        match self.next {
-            None => {None}
-            Some(ref next) => {Some(Rc::clone(next))}
+impl<K, V, S> LFUCache<K, V, S>
+where K: Hash + Eq, S: BuildHasher {
+    // Create a cache that hashes keys with the given hasher
+    pub fn with_hasher(max_size: usize, hash_builder: S) -> Self {
+        LFUCache::with_capacity_and_hasher(max_size, 0, hash_builder)
+    }
+
+    // Like `with_hasher`, but with room for `capacity` keys allocated
+    // up front
+    pub fn with_capacity_and_hasher(max_size: usize, capacity: usize, hash_builder: S) -> Self {
+        LFUCache {
+            policy: Box::new(LfuPolicy::new()),
+            nodes: Slab::with_capacity(capacity),
+            cache: HashMap::with_capacity_and_hasher(capacity, hash_builder),
+            max_size,
+            weight: 0,
+            weigher: None,
+            listener: None,
+            default_ttl: None,
+            expirations: BTreeSet::new(),
+            clock: Box::new(SystemClock),
+            aging_interval: None,
+            operations: 0,
+            admission: None
         }
     }
-    fn set_next(&mut self, new_next: Option<Rc<RefCell<CacheNode<K, V>>>>) {
-        self.next = new_next
+
+    pub fn hasher(&self) -> &S {
+        self.cache.hasher()
     }
-    fn get_prev(&self) -> Option<Weak<RefCell<CacheNode<K, V>>>> {
-        match self.prev {
-            None => {None}
-            Some(ref prev) => {Some(Rc::downgrade(&Rc::clone(&prev.upgrade().unwrap())))}
+
+    fn weigh(&self, key: &K, value: &V) -> usize {
+        match self.weigher {
+            None => 1,
+            Some(ref weigher) => weigher.weigh(key, value)
         }
     }
-    fn set_prev(&mut self, new_prev: Option<Weak<RefCell<CacheNode<K, V>>>>) {
-        self.prev = new_prev
+
+    // Register a listener that is called with every entry that leaves
+    // the cache, replacing any previous listener.
+    pub fn set_eviction_listener<L>(&mut self, listener: L)
+    where L: EvictionListener<K, V> + Send + 'static {
+        self.listener = Some(Box::new(listener));
     }
-}
 
-// A linked list of CacheNode objects with the same frequency. This
-// struct is also itself a node in a linked list of FrequencyList
-// objects.
-#[derive(Debug)]
-pub struct FrequencyList<K, V>
-where K: Hash + Eq + Clone {
-    head: Option<Rc<RefCell<CacheNode<K, V>>>>,
-    frequency: usize,
-    next: Option<Rc<RefCell<FrequencyList<K, V>>>>,
-    prev: Option<Weak<RefCell<FrequencyList<K, V>>>>,
-}
+    pub fn remove_eviction_listener(&mut self) {
+        self.listener = None;
+    }
+
+    // Tell the eviction listener, if there is one, that the given
+    // node is leaving the cache
+    fn notify_listener(&mut self, node: usize, reason: EvictionReason) {
+        if let Some(listener) = self.listener.as_mut() {
+            let node = &self.nodes[node];
+            listener.on_evict(&node.key, &node.value, node.frequency, reason);
+        }
+    }
 
-impl<K, V> FrequencyList<K, V>
-where K: Hash + Eq + Clone,
-      V: Display {
-    pub fn new(freq: usize) -> Self {
-        FrequencyList {
-            head: None,
-            frequency: freq,
-            next: None, prev: None
+    // Hand an entry that never made it into the cache straight to the
+    // eviction listener, returning it
+    fn discard(&mut self, key: K, value: V, frequency: usize, reason: EvictionReason) -> (K, V) {
+        if let Some(listener) = self.listener.as_mut() {
+            listener.on_evict(&key, &value, frequency, reason);
         }
+        (key, value)
     }
 
-    pub fn to_string(&self, cache: &LFUCache<K, V>) -> String
-    where K: Hash + Eq + Clone, V: Display {
-        self.reduce(|acc: String, node| {
-            let mut acc = acc.clone();
-            acc.push_str(&format!(" {}", node.borrow().get_associated_data(cache)));
-            acc
-        }, format!("Count {}:", self.frequency))
+    // The time to live given to entries inserted without an explicit
+    // one. None, the default, means they never expire.
+    pub fn default_ttl(&self) -> Option<Duration> {
+        self.default_ttl
+    }
+
+    // Change the default time to live. Entries already in the cache
+    // keep their expiry time.
+    pub fn set_default_ttl(&mut self, ttl: Option<Duration>) {
+        self.default_ttl = ttl;
     }
-}
 
-impl<K, V> nodes::Node for FrequencyList<K, V>
-where K: Hash + Eq + Clone {
-    fn get_next(&self) -> Option<Rc<RefCell<FrequencyList<K, V>>>> {
-        match self.next {
-            None => {None}
-            Some(ref next) => {Some(Rc::clone(next))}
+    // Replace the clock used to expire entries, e.g. with a
+    // ManualClock in tests. Expiry times already set were computed
+    // with the old clock.
+    pub fn set_clock<C>(&mut self, clock: C)
+    where C: Clock + Send + 'static {
+        self.clock = Box::new(clock);
+    }
+
+    // The expiry time of an entry with the given time to live,
+    // inserted now. A time to live longer than MAX_TTL_SECS never
+    // expires, so the expiry time can't overflow an Instant.
+    fn expiry_after(&self, ttl: Option<Duration>) -> Option<Instant> {
+        match ttl {
+            Some(ttl) if ttl <= Duration::from_secs(MAX_TTL_SECS) => Some(self.clock.now() + ttl),
+            _ => None
         }
     }
-    fn set_next(&mut self, new_next: Option<Rc<RefCell<FrequencyList<K, V>>>>) {
-        self.next = new_next
+
+    fn set_expiry(&mut self, node: usize, expires_at: Option<Instant>) {
+        if let Some(old_expires_at) = self.nodes[node].expires_at {
+            self.expirations.remove(&(old_expires_at, node));
+        }
+        if let Some(expires_at) = expires_at {
+            self.expirations.insert((expires_at, node));
+        }
+        self.nodes[node].expires_at = expires_at;
+        self.debug_check_invariants();
     }
-    fn get_prev(&self) -> Option<Weak<RefCell<FrequencyList<K, V>>>> {
-        match self.prev {
-            None => {None}
-            Some(ref prev) => {Some(Rc::downgrade(&Rc::clone(&prev.upgrade().unwrap())))}
+
+    fn is_expired(&self, node: usize) -> bool {
+        match self.nodes[node].expires_at {
+            None => false,
+            Some(expires_at) => expires_at <= self.clock.now()
         }
     }
-    fn set_prev(&mut self, new_prev: Option<Weak<RefCell<FrequencyList<K, V>>>>) {
-        self.prev = new_prev
+
+    // The node of the given key, unless it has expired
+    fn live_node<Q>(&self, key: &Q) -> Option<usize>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        let node = *self.cache.get(Lookup::new(key))?;
+        if self.is_expired(node) { None } else { Some(node) }
     }
-}
 
-impl<K, V> nodes::HasHead for FrequencyList<K, V>
-where K: Hash + Eq + Clone {
-    type Element = CacheNode<K, V>;
-    fn get_head(&self) -> Option<Rc<RefCell<CacheNode<K, V>>>> {
-        match self.head {
-            None => {None}
-            Some(ref head) => {Some(Rc::clone(head))}
+    // The node of the given key. An expired node is removed instead.
+    fn live_node_mut<Q>(&mut self, key: &Q) -> Option<usize>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        let node = *self.cache.get(Lookup::new(key))?;
+        if self.is_expired(node) {
+            self.remove_node(node, EvictionReason::Expired);
+            None
+        } else {
+            Some(node)
         }
     }
-    fn set_head(&mut self, new_head: Option<Rc<RefCell<CacheNode<K, V>>>>) {
-        self.head = new_head
+
+    // The node that expired first, other than `keep`, if any node has
+    // expired
+    fn first_expired_node(&self, keep: Option<usize>) -> Option<usize> {
+        if self.expirations.is_empty() {
+            return None;
+        }
+        let now = self.clock.now();
+        self.expirations.iter()
+            .take_while(|&&(expires_at, _)| expires_at <= now)
+            .map(|&(_, node)| node)
+            .find(|&node| Some(node) != keep)
     }
-}
 
-// This is the main struct and the entrypoint to the cache.
-#[derive(Debug)]
-pub struct LFUCache<K, V>
-where K: Hash + Eq + Clone {
-    frequency_list_head: Option<Rc<RefCell<FrequencyList<K, V>>>>,
-    cache: HashMap<K, (V, Rc<RefCell<CacheNode<K, V>>>)>,
-    max_size: usize
-}
+    // Remove every expired entry, returning them in the order they
+    // expired. Expired entries are otherwise only removed when they
+    // are looked up or when room is needed, so until then they still
+    // count towards `len` and show up when iterating.
+    pub fn purge_expired(&mut self) -> Vec<(K, V)> {
+        let mut expired = Vec::new();
+        while let Some(node) = self.first_expired_node(None) {
+            expired.push(self.remove_node(node, EvictionReason::Expired));
+        }
+        expired
+    }
 
-impl<K, V> LFUCache<K, V>
-where K: Hash + Eq + Clone, V: Display {
-    pub fn new(max_size: usize) -> Self {
-        LFUCache {
-            frequency_list_head: None,
-            cache: HashMap::new(),
-            max_size: max_size
+    // The number of uses and insertions between halving all
+    // frequencies. None, the default, disables aging.
+    pub fn aging_interval(&self) -> Option<usize> {
+        self.aging_interval
+    }
+
+    // Enable or disable aging. Halving walks every frequency list, so
+    // an interval of at least the cache's capacity keeps `get` and
+    // `insert` O(1) amortized. An interval of 0 disables aging, like
+    // None.
+    pub fn set_aging_interval(&mut self, interval: Option<usize>) {
+        self.aging_interval = interval.filter(|&interval| interval > 0);
+        self.operations = 0;
+    }
+
+    // Halve the frequency of every key, rounding down but never below
+    // 1, and let the eviction policy know. LfuPolicy halves its lists
+    // along with the frequencies; lists that become equal are merged,
+    // with the keys that used to be less frequently used evicted first.
+    pub fn age(&mut self) {
+        self.operations = 0;
+        for (_, node) in self.nodes.iter_mut() {
+            node.frequency = cmp::max(1, node.frequency / 2);
+        }
+        self.policy.on_age();
+        self.debug_check_invariants();
+    }
+
+    // Count a use or insertion, aging the cache once the aging
+    // interval is reached
+    fn tick(&mut self) {
+        if let Some(interval) = self.aging_interval {
+            self.operations += 1;
+            if self.operations >= interval {
+                self.age();
+            }
         }
     }
 
@@ -140,219 +335,422 @@ where K: Hash + Eq + Clone, V: Display {
         self.cache.len()
     }
 
-    pub fn to_string(&self) -> String {
-        if self.frequency_list_head.is_none() {
-            "<empty>".to_string()
-        } else {
-            self.reduce(|acc: String, list| {
-                let mut acc = acc.clone();
-                acc.push_str(&format!("{}\n", list.borrow().to_string(&self)));
-                acc
-            }, "".to_string())
-        }
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
     }
 
-    // Given a node in the cache that was recently used, increment
-    // this node's frequency by moving it ahead to the next frequency
-    // list.
-    fn increment_node_frequency(&mut self, node: Rc<RefCell<CacheNode<K, V>>>) {
-        // Create and initialize the new parent list. This might be an
-        // existing list, or we might need to create a new one.
-        let new_parent = {
-            let node = node.borrow();
-
-            let current_frequency = node.parent.borrow().frequency;
-
-            let there_is_a_gap = {
-                let next_list = &node.parent.borrow().next;
-                next_list.is_some() && next_list.as_ref().unwrap().borrow().frequency
-                    != current_frequency + 1
-            };
+    // The total weight of the entries in the cache. This is the same
+    // as `len` if the cache has no weigher.
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
 
-            // if either the next frequency does doesn't exist (this is
-            // the last one) or the next frequency list's frequency is not
-            // the current frequency + 1 (there is a gap), we need to
-            // create a new frequency list and add this node to it.
-            let new_parent = if node.parent.borrow().next.is_none() || there_is_a_gap {
-                // create new parent list and connect it to the old parent
-                Rc::new(RefCell::new(FrequencyList::new(current_frequency + 1)))
-            } else {
-                Rc::clone(node.parent.borrow().next.as_ref().unwrap())
-            };
+    // The maximum total weight the cache holds; the maximum number of
+    // keys if the cache has no weigher.
+    pub fn capacity(&self) -> usize {
+        self.max_size
+    }
 
-            // if the next frequency list did exist, but there was a
-            // gap, we need to point it back to the new parent
-            if there_is_a_gap {
-                let next_list = &node.parent.borrow().next;
-                next_list.as_ref().unwrap()
-                    .borrow_mut().prev = Some(Rc::downgrade(&new_parent));
-                new_parent.borrow_mut().next = Some(Rc::clone(
-                    next_list.as_ref().unwrap()
-                ));
-            }
+    // Change the maximum total weight the cache holds. When
+    // shrinking, the least frequently used keys are evicted until the
+    // cache fits; they are returned in eviction order and passed to
+    // the eviction listener.
+    pub fn set_capacity(&mut self, max_size: usize) -> Vec<(K, V)> {
+        self.max_size = max_size;
+        self.evict_to_fit(0, None)
+    }
 
-            // Point the new parent back to the old parent in case
-            // they are not connected.
-            new_parent.borrow_mut().prev = Some(Rc::downgrade(&node.parent));
-            node.parent.borrow_mut().next = Some(Rc::clone(&new_parent));
+    // Set or remove the admission filter. Without one, the default,
+    // every new key is admitted. Keys inserted through the entry API
+    // are always admitted, since a reference to the value has to be
+    // returned.
+    pub fn set_admission_filter(&mut self, filter: Option<TinyLfu>) {
+        self.admission = filter;
+    }
 
-            new_parent
-        };
+    pub fn admission_filter(&self) -> Option<&TinyLfu> {
+        self.admission.as_ref()
+    }
 
-        // if this is the last node in it's current parent, we
-        // need to remove the current parent
-        if node.borrow().next.is_none() && node.borrow().prev.is_none() {
-            let node = node.borrow();
-            node.parent.borrow_mut().remove();
+    // Record a lookup or insertion of the given key with the
+    // admission filter, if there is one
+    fn record_access<Q>(&mut self, key: &Q)
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        if let Some(filter) = self.admission.as_mut() {
+            filter.record(key);
         }
+    }
 
-        // if this node is the head, we need to advance the head of
-        // it's parent
-        if node.borrow().prev.is_none() {
-            let node_parent = Rc::clone(&node.borrow().parent);
-            node_parent.borrow_mut().pop_head();
+    // Whether a new key of the given weight may be inserted. It always
+    // may if it fits without evicting a live key, or if there is no
+    // admission filter. Otherwise it has to beat the first live key
+    // the policy would evict.
+    fn admit(&self, key: &K, weight: usize) -> bool {
+        let filter = match self.admission {
+            Some(ref filter) => filter,
+            None => return true
+        };
+        let needed = self.weight.saturating_add(weight).saturating_sub(self.max_size);
+        if needed <= self.expired_weight(needed) {
+            return true;
         }
+        let mut victim = self.first_node();
+        while let Some(node) = victim {
+            if !self.is_expired(node) {
+                return filter.admit(key, &*self.nodes[node].key);
+            }
+            victim = self.node_after(node);
+        }
+        true
+    }
 
-        // set the parent as the new parent
-        node.borrow_mut().parent = Rc::clone(&new_parent);
-
-        // if this is the frequency list head, we need to repoint the head to the next list.
-        if self.frequency_list_head.as_ref().unwrap().borrow().head.is_none() {
-            self.frequency_list_head = Some(Rc::clone(&new_parent));
+    // The weight of the expired entries, counting only until `needed`
+    // is reached
+    fn expired_weight(&self, needed: usize) -> usize {
+        let now = self.clock.now();
+        let mut weight = 0;
+        for &(expires_at, node) in &self.expirations {
+            if expires_at > now || weight >= needed {
+                break;
+            }
+            weight += self.nodes[node].weight;
         }
+        weight
+    }
+
+    // Given a node in the cache that was recently used, increment
+    // this node's frequency and tell the eviction policy
+    fn increment_node_frequency(&mut self, node: usize) {
+        self.tick();
+        self.nodes[node].frequency += 1;
+        self.policy.on_access(node);
+        self.debug_check_invariants();
+    }
+
+    // Remove the given node from the internal cache structures,
+    // returning its key and value
+    fn remove_node(&mut self, node: usize, reason: EvictionReason) -> (K, V) {
+        self.notify_listener(node, reason);
+        self.set_expiry(node, None);
+        self.policy.on_remove(node, reason);
+        let node = self.nodes.remove(node);
+        self.cache.remove(Lookup::new(&*node.key));
+        self.weight -= node.weight;
+        self.debug_check_invariants();
+        (node.key.into_inner(), node.value)
+    }
 
-        // remove this node from it's list and add it to its new parent
-        node.borrow_mut().remove();
-        new_parent.borrow_mut().push(Rc::clone(&node));
+    // Replace the value of the given node with one of the given
+    // weight, returning the old one. Nothing is evicted.
+    fn replace_value(&mut self, node: usize, value: V, weight: usize) -> V {
+        self.notify_listener(node, EvictionReason::Replaced);
+        self.weight = self.weight - self.nodes[node].weight + weight;
+        self.nodes[node].weight = weight;
+        let old_value = mem::replace(&mut self.nodes[node].value, value);
+        self.debug_check_invariants();
+        old_value
     }
 
-    // remove the given node from the internal cache structures
-    fn remove_node(&mut self, node: Rc<RefCell<CacheNode<K, V>>>) {
-        if node.borrow().is_head() {
-            let node_parent = {
-                Rc::clone(&node.borrow().parent)
+    // Evict keys until `extra` more weight fits within the capacity,
+    // never evicting the `keep` node. Expired keys go first, then the
+    // ones the eviction policy chooses. Returns the evicted entries in
+    // eviction order.
+    fn evict_to_fit(&mut self, extra: usize, keep: Option<usize>) -> Vec<(K, V)> {
+        let mut evicted = Vec::new();
+        while self.weight.saturating_add(extra) > self.max_size {
+            if let Some(node) = self.first_expired_node(keep) {
+                evicted.push(self.remove_node(node, EvictionReason::Expired));
+                continue;
+            }
+            let victim = match self.first_node() {
+                Some(node) if Some(node) == keep => self.node_after(node),
+                node => node
             };
-            if node.borrow().is_only_child() {
-                node_parent.borrow_mut().remove();
+            match victim {
+                None => break,
+                Some(victim) => evicted.push(self.remove_node(victim, EvictionReason::Capacity))
             }
-            node_parent.borrow_mut().pop_head();
         }
+        evicted
+    }
+
+    // Link a key that is not yet in the cache in with a frequency of
+    // 1, without evicting anything. Returns the new node.
+    fn insert_new(&mut self, key: K, value: V, weight: usize) -> usize {
+        self.tick();
+        let key = SharedKey::new(key);
+        let shared = key.share();
+        let node = self.nodes.insert(CacheNode {
+            key, value, weight, frequency: 1, expires_at: None
+        });
+        self.policy.on_insert(node);
+        self.cache.insert(shared, node);
+        self.weight += weight;
+        self.debug_check_invariants();
+        node
+    }
+
+    // Get the value associated with the given key. Like HashMap, the
+    // key may be any borrowed form of the cache's key type, e.g. a
+    // &str for String keys.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        self.record_access(key);
+        let node = self.live_node_mut(key)?;
+        self.increment_node_frequency(node);
+        Some(&self.nodes[node].value)
+    }
+
+    // Get a mutable reference to the value associated with the given
+    // key. Like `get`, this counts as a use of the key.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        self.record_access(key);
+        let node = self.live_node_mut(key)?;
+        self.increment_node_frequency(node);
+        Some(&mut self.nodes[node].value)
+    }
 
-        node.borrow_mut().remove();
+    // Get the value associated with the given key without counting
+    // it as a use. The eviction order is left unchanged.
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        let node = self.live_node(key)?;
+        Some(&self.nodes[node].value)
     }
 
-    // Get the value associated with the given key
-    pub fn get(&mut self, key: &K) -> Option<&V> {
-        let node = Rc::clone(&self.cache.get(key)?.1);
-        self.increment_node_frequency(Rc::clone(&node));
+    // Get a mutable reference to the value associated with the given
+    // key without counting it as a use.
+    pub fn peek_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        let node = self.live_node(key)?;
+        Some(&mut self.nodes[node].value)
+    }
 
-        let (data, _) = self.cache.get(key)?;
-        Some(&data)
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        self.live_node(key).is_some()
+    }
+
+    // Get the number of times the given key has been used, without
+    // counting this as a use.
+    pub fn frequency<Q>(&self, key: &Q) -> Option<usize>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        let node = self.live_node(key)?;
+        Some(self.nodes[node].frequency)
     }
 
     // Remove the value associated with the given key.
-    pub fn remove(&mut self, key: &K) -> Option<V> {
-        let node = Rc::clone(&self.cache.get(&key)?.1);
-        self.remove_node(Rc::clone(&node));
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        let node = self.live_node_mut(key)?;
+        Some(self.remove_node(node, EvictionReason::Removed).1)
+    }
+
+    // Get the given key's entry for in-place manipulation. Looking up
+    // a key that is already in the cache counts as one use of that
+    // key, exactly like `get`; the methods on the returned entry
+    // don't change its frequency again. Inserting through a vacant
+    // entry behaves like `insert`.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+        self.record_access(&key);
+        match self.live_node_mut(&key) {
+            Some(node) => {
+                self.increment_node_frequency(node);
+                Entry::Occupied(OccupiedEntry::new(self, node))
+            }
+            None => Entry::Vacant(VacantEntry::new(self, key))
+        }
+    }
+
+    // The key, value and frequency of the live entry that would be
+    // evicted next, without counting this as a use. Expired entries
+    // are skipped, since they go first.
+    pub fn peek_lfu(&self) -> Option<(&K, &V, usize)> {
+        self.iter().find(|&(key, _, _)| self.live_node(key).is_some())
+    }
+
+    // The key, value and frequency of the live entry that would be
+    // evicted last, without counting this as a use
+    pub fn peek_mfu(&self) -> Option<(&K, &V, usize)> {
+        self.iter().rev().find(|&(key, _, _)| self.live_node(key).is_some())
+    }
+
+    // Remove the entry that would be evicted next. Expired entries
+    // are purged first.
+    pub fn pop_lfu(&mut self) -> Option<(K, V)> {
+        self.purge_expired();
+        let node = self.first_node()?;
+        Some(self.remove_node(node, EvictionReason::Removed))
+    }
+
+    // Remove the entry that would be evicted last. Expired entries
+    // are purged first.
+    pub fn pop_mfu(&mut self) -> Option<(K, V)> {
+        self.purge_expired();
+        let node = self.last_node()?;
+        Some(self.remove_node(node, EvictionReason::Removed))
+    }
+
+    // Iterate over the keys, values and frequencies in the cache in
+    // eviction order, from the least to the most frequently used key.
+    // Use `rev` for the opposite order. Iterating doesn't count as a
+    // use of any key.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter::new(self)
+    }
+
+    // Like `iter`, but with mutable references to the values
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut::new(self)
+    }
+
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys::new(self.iter())
+    }
 
-        let (data, _) = self.cache.remove(&key)?;
-        Some(data)
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values::new(self.iter())
     }
 
     // Insert the value associated with the given key. If this
     // operations means that the cache size will be greater than the
     // max size, evict the least frequently used key.
     pub fn insert(&mut self, key: K, value: V) -> Option<V> {
-        if !self.cache.contains_key(&key) {
-            // Get the first frequency list if it exists
-            match self.pop_head() {
-                None => {
-                    // create a new list and a new node, connect them.
-                    let mut new_frequency_list = Rc::new(RefCell::new(
-                        FrequencyList::new(1)
-                    ));
-                    let new_node = Rc::new(RefCell::new(CacheNode {
-                        parent: Rc::clone(&new_frequency_list),
-                        key: key.clone(), next: None, prev: None
-                    }));
-                    new_frequency_list.borrow_mut().push(Rc::clone(&new_node));
-
-                    // set the new list as the freq list head, insert
-                    // the new node and value into the hashmap
-                    self.push(new_frequency_list);
-                    self.cache.insert(key, (value, Rc::clone(&new_node)));
-                }
-                Some(list) => {
-                    // remove LFU item if we are over the max size
-                    if self.len() >= self.max_size {
-                        let key = {
-                            let node_to_remove = &list.borrow().head;
-                            let node_to_remove = Rc::clone(
-                                &node_to_remove.as_ref().unwrap()
-                            );
-                            let node_to_remove = node_to_remove.borrow();
-                            node_to_remove.key.clone()
-                        };
-                        self.remove(&key);
-                    }
-
-                    // if the first list's frequency is 1, we can use it.
-                    if list.borrow().frequency == 1 {
-                        // create a new node and link it to the existing list
-                        let new_node = Rc::new(RefCell::new(CacheNode {
-                            parent: Rc::clone(&list),
-                            key: key.clone(), next: None, prev: None
-                        }));
-                        list.borrow_mut().push(Rc::clone(&new_node));
-
-                         // set the head back to the original list
-                        self.push(Rc::clone(&list));
-
-                        // insert the new data into the map
-                        self.cache.insert(key, (value, Rc::clone(&new_node)));
-                    } else {
-                        // the existing list did not have a frequency
-                        // of 1 - we can't use it.
-                        let new_frequency_list = Rc::new(RefCell::new(
-                            FrequencyList::new(1)
-                        ));
-                        let new_node = Rc::new(RefCell::new(CacheNode {
-                            parent: Rc::clone(&new_frequency_list),
-                            key: key.clone(), next: None, prev: None
-                        }));
-
-                        // Push the new node onto the new list, push
-                        // the new list onto the cache.
-                        new_frequency_list.borrow_mut().push(Rc::clone(&new_node));
-                        self.push(Rc::clone(&new_frequency_list));
-
-                        // insert the new data into the map
-                        self.cache.insert(key, (value, Rc::clone(&new_node)));
-                    }
-                }
+        self.insert_with_eviction(key, value).replaced
+    }
+
+    // Like `insert`, but the entry expires after the given time to
+    // live instead of the default one
+    pub fn insert_with_ttl(&mut self, key: K, value: V, ttl: Duration) -> Option<V> {
+        self.insert_expiring(key, value, Some(ttl)).replaced
+    }
+
+    // Like `insert`, but also hands back the entries that were
+    // evicted to make room. An entry heavier than the whole capacity
+    // is not kept: it is evicted straight away, without evicting
+    // anything else, and handed back along with the other evicted
+    // entries.
+    pub fn insert_with_eviction(&mut self, key: K, value: V) -> InsertResult<K, V> {
+        let ttl = self.default_ttl;
+        self.insert_expiring(key, value, ttl)
+    }
+
+    // Insert the given entry with the given time to live. Replacing a
+    // value also resets its expiry time.
+    fn insert_expiring(&mut self, key: K, value: V, ttl: Option<Duration>) -> InsertResult<K, V> {
+        let expires_at = self.expiry_after(ttl);
+        self.record_access(&key);
+        if let Some(node) = self.live_node_mut(&key) {
+            self.increment_node_frequency(node);
+            let weight = self.weigh(&key, &value);
+            if weight > self.max_size {
+                // The old value is replaced and the new one doesn't
+                // fit. Neither was evicted to make room, so the
+                // policy only hears that the key was replaced.
+                let frequency = self.nodes[node].frequency;
+                let (key, old_value) = self.remove_node(node, EvictionReason::Replaced);
+                let evicted = vec![self.discard(key, value, frequency, EvictionReason::Capacity)];
+                return InsertResult { replaced: Some(old_value), evicted };
             }
+            let replaced = Some(self.replace_value(node, value, weight));
+            self.set_expiry(node, expires_at);
+            let evicted = self.evict_to_fit(0, Some(node));
+            return InsertResult { replaced, evicted };
+        }
 
-            None
+        let weight = self.weigh(&key, &value);
+        let evicted = if weight > self.max_size {
+            vec![self.discard(key, value, 1, EvictionReason::Capacity)]
+        } else if !self.admit(&key, weight) {
+            let node = self.insert_new(key, value, weight);
+            vec![self.remove_node(node, EvictionReason::Rejected)]
         } else {
-            let (old_value, node) = self.cache.remove(&key).unwrap();
-            self.increment_node_frequency(Rc::clone(&node));
-            self.cache.insert(key, (value, Rc::clone(&node)));
-            Some(old_value)
-        }
+            let evicted = self.evict_to_fit(weight, None);
+            let node = self.insert_new(key, value, weight);
+            self.set_expiry(node, expires_at);
+            evicted
+        };
+        InsertResult { replaced: None, evicted }
+    }
+}
+
+// Traversal of the nodes in eviction order
+impl<K, V, S> LFUCache<K, V, S>
+where K: Hash + Eq {
+    fn first_node(&self) -> Option<usize> {
+        self.policy.choose_victim()
+    }
+
+    fn last_node(&self) -> Option<usize> {
+        self.policy.last()
     }
 
+    fn node_after(&self, node: usize) -> Option<usize> {
+        self.policy.next(node)
+    }
 }
 
-impl<K, V> nodes::HasHead for LFUCache<K, V>
-where K: Hash + Eq + Clone {
-    type Element = FrequencyList<K, V>;
-    fn get_head(&self) -> Option<Rc<RefCell<FrequencyList<K, V>>>> {
-        match self.frequency_list_head {
-            None => {None}
-            Some(ref head) => {Some(Rc::clone(head))}
-        }
+impl<'a, K, V, S> IntoIterator for &'a LFUCache<K, V, S>
+where K: Hash + Eq {
+    type Item = (&'a K, &'a V, usize);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        Iter::new(self)
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a mut LFUCache<K, V, S>
+where K: Hash + Eq {
+    type Item = (&'a K, &'a mut V, usize);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> IterMut<'a, K, V> {
+        IterMut::new(self)
+    }
+}
+
+// Consume the cache, yielding its keys, values and frequencies in
+// eviction order
+impl<K, V, S> IntoIterator for LFUCache<K, V, S>
+where K: Hash + Eq {
+    type Item = (K, V, usize);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> IntoIter<K, V> {
+        IntoIter::new(self)
     }
-    fn set_head(&mut self, new_head: Option<Rc<RefCell<FrequencyList<K, V>>>>) {
-        self.frequency_list_head = new_head
+}
+
+// Formats the cache like a map, in eviction order
+impl<K, V, S> fmt::Debug for LFUCache<K, V, S>
+where K: Hash + Eq + fmt::Debug, V: fmt::Debug, S: BuildHasher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter().map(|(key, value, _)| (key, value))).finish()
+    }
+}
+
+// Displays the values in eviction order, starting a new line for
+// each run of keys with the same frequency. With the default policy
+// that is one line per frequency.
+impl<K, V, S> Display for LFUCache<K, V, S>
+where K: Hash + Eq, V: Display, S: BuildHasher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "<empty>");
+        }
+        let mut current = None;
+        for (_, value, frequency) in self.iter() {
+            if current != Some(frequency) {
+                if current.is_some() {
+                    writeln!(f)?;
+                }
+                write!(f, "Count {}:", frequency)?;
+                current = Some(frequency);
+            }
+            write!(f, " {}", value)?;
+        }
+        writeln!(f)
     }
 }
@@ -1,69 +1,174 @@
-use std::rc::{Rc, Weak};
-use std::cell::RefCell;
-
-// Trait for objects that contain a reference to the head of a linked list
-pub trait HasHead
-where Self: Sized {
-    type Element: Node;
-    fn get_head(&self) -> Option<Rc<RefCell<Self::Element>>>;
-    fn set_head(&mut self, new_head: Option<Rc<RefCell<Self::Element>>>);
-
-    fn push(&mut self, node: Rc<RefCell<Self::Element>>) {
-        match self.get_head() {
+use std::ops::{Index, IndexMut};
+
+use InvariantError;
+
+// A single slot in a Slab. Vacant slots form a singly linked free
+// list through the index they hold.
+#[derive(Debug, Clone)]
+enum Slot<T> {
+    Occupied(T),
+    Vacant(Option<usize>),
+}
+
+// Storage for values addressed by a stable index. Removed slots are
+// put on a free list and reused by later insertions, so a cache that
+// has reached its steady state no longer allocates.
+#[derive(Debug, Clone)]
+pub struct Slab<T> {
+    slots: Vec<Slot<T>>,
+    next_free: Option<usize>,
+    len: usize,
+}
+
+impl<T> Slab<T> {
+    pub fn new() -> Self {
+        Slab {
+            slots: Vec::new(),
+            next_free: None,
+            len: 0,
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Slab {
+            slots: Vec::with_capacity(capacity),
+            next_free: None,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Store a value and return the index it can be retrieved with.
+    pub fn insert(&mut self, value: T) -> usize {
+        self.len += 1;
+        match self.next_free {
             None => {
-                node.borrow_mut().set_next(None);
-                node.borrow_mut().set_prev(None);
-                self.set_head(Some(node));
+                self.slots.push(Slot::Occupied(value));
+                self.slots.len() - 1
             }
-            Some(ref head) => {
-                head.borrow_mut().set_prev(Some(Rc::downgrade(&node)));
-                node.borrow_mut().set_next(Some(Rc::clone(head)));
-                node.borrow_mut().set_prev(None);
-                self.set_head(Some(node));
+            Some(index) => {
+                match self.slots[index] {
+                    Slot::Vacant(next_free) => self.next_free = next_free,
+                    Slot::Occupied(_) => unreachable!("free list points at an occupied slot"),
+                }
+                self.slots[index] = Slot::Occupied(value);
+                index
             }
         }
     }
 
-    fn pop_head(&mut self) -> Option<Rc<RefCell<Self::Element>>> {
-        if self.get_head().is_none() {None}
-        else {
-            let head = self.get_head().unwrap();
+    // Remove the value at the given index, freeing the slot for
+    // reuse. Panics if the slot is not occupied.
+    pub fn remove(&mut self, index: usize) -> T {
+        match self.slots.get(index) {
+            Some(&Slot::Occupied(_)) => {}
+            _ => panic!("invalid slab index {}", index),
+        }
+        self.len -= 1;
+        let slot = ::std::mem::replace(&mut self.slots[index], Slot::Vacant(self.next_free));
+        self.next_free = Some(index);
+        match slot {
+            Slot::Occupied(value) => value,
+            Slot::Vacant(_) => unreachable!(),
+        }
+    }
 
-            match head.borrow().get_next() {
-                None => {
-                    self.set_head(None);
-                }
-                Some(ref new_head) => {
-                    new_head.borrow_mut().set_prev(None);
-                    self.set_head(Some(Rc::clone(new_head)));
-                }
-            }
+    pub fn get(&self, index: usize) -> Option<&T> {
+        match self.slots.get(index) {
+            Some(Slot::Occupied(value)) => Some(value),
+            _ => None,
+        }
+    }
 
-            head.borrow_mut().set_next(None);
-            Some(Rc::clone(&head))
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        match self.slots.get_mut(index) {
+            Some(Slot::Occupied(value)) => Some(value),
+            _ => None,
         }
     }
 
-    fn reduce<U, F>(&self, f: F, initial: U) -> U
-    where F: Fn(U, Rc<RefCell<Self::Element>>) -> U {
-        match self.get_head() {
-            None => {initial}
-            Some(ref head) => {
-                let out = f(initial, Rc::clone(head));
-                head.borrow().reduce_forward(f, out)
-            }
+    pub fn contains(&self, index: usize) -> bool {
+        self.get(index).is_some()
+    }
+
+    // Iterate over the indices and values of all occupied slots, in
+    // index order
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| match slot {
+            Slot::Occupied(value) => Some((index, value)),
+            Slot::Vacant(_) => None,
+        })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut T)> {
+        self.slots.iter_mut().enumerate().filter_map(|(index, slot)| match slot {
+            Slot::Occupied(value) => Some((index, value)),
+            Slot::Vacant(_) => None,
+        })
+    }
+
+    // The number of slots, occupied or not. Every index handed out by
+    // `insert` is below this.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    // Consume the slab, returning a vector indexed like the slab with
+    // None for every vacant slot
+    pub fn into_slots(self) -> Vec<Option<T>> {
+        self.slots.into_iter().map(|slot| match slot {
+            Slot::Occupied(value) => Some(value),
+            Slot::Vacant(_) => None,
+        }).collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.next_free = None;
+        self.len = 0;
+    }
+}
+
+impl<T> Default for Slab<T> {
+    fn default() -> Self {
+        Slab::new()
+    }
+}
+
+impl<T> Index<usize> for Slab<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        match self.get(index) {
+            Some(value) => value,
+            None => panic!("invalid slab index {}", index),
         }
     }
 }
 
-// Trait for objects that are nodes in a linked list
-pub trait Node
-where Self: Sized {
+impl<T> IndexMut<usize> for Slab<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        match self.get_mut(index) {
+            Some(value) => value,
+            None => panic!("invalid slab index {}", index),
+        }
+    }
+}
 
-    fn get_next(&self) -> Option<Rc<RefCell<Self>>>;
-    fn set_next(&mut self, new_next: Option<Rc<RefCell<Self>>>);
-    fn get_prev(&self) -> Option<Weak<RefCell<Self>>>;
-    fn set_prev(&mut self, new_prev: Option<Weak<RefCell<Self>>>);
+// Trait for objects that are nodes in a linked list stored in a Slab.
+// Links are slab indices rather than pointers.
+pub trait Node {
+    fn get_next(&self) -> Option<usize>;
+    fn set_next(&mut self, new_next: Option<usize>);
+    fn get_prev(&self) -> Option<usize>;
+    fn set_prev(&mut self, new_prev: Option<usize>);
 
     fn is_head(&self) -> bool {
         self.get_prev().is_none()
@@ -72,58 +177,168 @@ where Self: Sized {
     fn is_only_child(&self) -> bool {
         self.get_prev().is_none() && self.get_next().is_none()
     }
+}
 
-    // Fold left from this node
-    fn reduce_forward<U, F>(&self, f: F, out: U) -> U
-    where F: Fn(U, Rc<RefCell<Self>>) -> U {
-        match self.get_next() {
-            None => {out}
-            Some(ref next) => {
-                let out = f(out, Rc::clone(next));
-                next.borrow().reduce_forward(f, out)
-            }
+// The two ends of a doubly linked list whose nodes live in a Slab, or
+// in any other storage indexed by usize, such as a Vec. Every
+// operation is O(1) except iteration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct List {
+    head: Option<usize>,
+    tail: Option<usize>,
+}
+
+impl List {
+    pub fn new() -> Self {
+        List { head: None, tail: None }
+    }
+
+    pub fn head(&self) -> Option<usize> {
+        self.head
+    }
+
+    pub fn tail(&self) -> Option<usize> {
+        self.tail
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    // Link the node at `index` in as the new head of the list
+    pub fn push_front<T, S>(&mut self, slab: &mut S, index: usize)
+    where T: Node, S: IndexMut<usize, Output = T> + ?Sized {
+        slab[index].set_prev(None);
+        slab[index].set_next(self.head);
+        match self.head {
+            None => self.tail = Some(index),
+            Some(head) => slab[head].set_prev(Some(index)),
         }
+        self.head = Some(index);
     }
 
-    fn remove(&mut self) {
-        match self.get_next() {
-            None => {}
-            Some(ref next) => {
-                match self.get_prev() {
-                    None => {next.borrow_mut().set_prev(None);}
-                    Some(ref prev) => {
-                        next.borrow_mut().set_prev(Some(
-                            Rc::downgrade(&Rc::clone(&prev.upgrade().unwrap()))
-                        ));
-                    }
-                }
-            }
+    // Link the node at `index` in as the new tail of the list
+    pub fn push_back<T, S>(&mut self, slab: &mut S, index: usize)
+    where T: Node, S: IndexMut<usize, Output = T> + ?Sized {
+        slab[index].set_next(None);
+        slab[index].set_prev(self.tail);
+        match self.tail {
+            None => self.head = Some(index),
+            Some(tail) => slab[tail].set_next(Some(index)),
         }
-        match self.get_prev() {
-            None => {}
-            Some(ref prev) => {
-                let prev = prev.upgrade().unwrap();
-                match self.get_next() {
-                    None => {prev.borrow_mut().set_next(None);}
-                    Some(ref next) => {
-                        prev.borrow_mut().set_next(Some(Rc::clone(next)));
-                    }
-                }
-            }
+        self.tail = Some(index);
+    }
+
+    // Link the node at `index` in directly after the node at `after`,
+    // which must already be in this list.
+    pub fn insert_after<T, S>(&mut self, slab: &mut S, after: usize, index: usize)
+    where T: Node, S: IndexMut<usize, Output = T> + ?Sized {
+        let next = slab[after].get_next();
+        slab[index].set_prev(Some(after));
+        slab[index].set_next(next);
+        slab[after].set_next(Some(index));
+        match next {
+            None => self.tail = Some(index),
+            Some(next) => slab[next].set_prev(Some(index)),
         }
-        self.set_next(None);
-        self.set_prev(None);
     }
 
-    // Get the depth from this node to the list head
-    fn get_depth_from_node(&self, node: Rc<RefCell<Self>>, depth: usize) -> usize {
-        match node.borrow().get_prev() {
-            Some(ref prev) => {
-                self.get_depth_from_node(prev.upgrade().unwrap(), depth + 1)
-            }
-            None => {
-                depth
+    // Unlink the node at `index` from this list. The node itself is
+    // left in the slab.
+    pub fn remove<T, S>(&mut self, slab: &mut S, index: usize)
+    where T: Node, S: IndexMut<usize, Output = T> + ?Sized {
+        let prev = slab[index].get_prev();
+        let next = slab[index].get_next();
+        match prev {
+            None => self.head = next,
+            Some(prev) => slab[prev].set_next(next),
+        }
+        match next {
+            None => self.tail = prev,
+            Some(next) => slab[next].set_prev(prev),
+        }
+        slab[index].set_next(None);
+        slab[index].set_prev(None);
+    }
+
+    pub fn pop_front<T, S>(&mut self, slab: &mut S) -> Option<usize>
+    where T: Node, S: IndexMut<usize, Output = T> + ?Sized {
+        let head = self.head?;
+        self.remove(slab, head);
+        Some(head)
+    }
+
+    // Walk the list from head to tail, checking that every node links
+    // back to the one before it and that the last one is the tail.
+    // `get` looks nodes up without panicking on a bad index. Returns
+    // the indices in order.
+    pub fn validate<'a, T, F>(&self, get: F) -> Result<Vec<usize>, InvariantError>
+    where T: Node + 'a, F: Fn(usize) -> Option<&'a T> {
+        let mut order = Vec::new();
+        let mut prev = None;
+        let mut current = self.head;
+        while let Some(index) = current {
+            let node = get(index).ok_or_else(|| {
+                InvariantError::new(format!("a list links to missing node {}", index))
+            })?;
+            if node.get_prev() != prev {
+                return Err(InvariantError::new(format!(
+                    "node {} links back to {:?} instead of {:?}", index, node.get_prev(), prev)));
             }
+            order.push(index);
+            prev = Some(index);
+            current = node.get_next();
+        }
+        if self.tail != prev {
+            return Err(InvariantError::new(format!(
+                "a list ends at {:?} but its tail is {:?}", prev, self.tail)));
+        }
+        Ok(order)
+    }
+
+    // Iterate over the indices and values of the list from head to tail
+    pub fn iter<'a, T: Node>(&self, slab: &'a Slab<T>) -> Iter<'a, T> {
+        Iter {
+            slab,
+            front: self.head,
+            back: self.tail,
+        }
+    }
+}
+
+// Iterator over the nodes of a List, see List::iter
+pub struct Iter<'a, T: 'a> {
+    slab: &'a Slab<T>,
+    front: Option<usize>,
+    back: Option<usize>,
+}
+
+impl<'a, T: Node> Iterator for Iter<'a, T> {
+    type Item = (usize, &'a T);
+
+    fn next(&mut self) -> Option<(usize, &'a T)> {
+        let index = self.front?;
+        let node = &self.slab[index];
+        if self.front == self.back {
+            self.front = None;
+            self.back = None;
+        } else {
+            self.front = node.get_next();
+        }
+        Some((index, node))
+    }
+}
+
+impl<'a, T: Node> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<(usize, &'a T)> {
+        let index = self.back?;
+        let node = &self.slab[index];
+        if self.front == self.back {
+            self.front = None;
+            self.back = None;
+        } else {
+            self.back = node.get_prev();
         }
+        Some((index, node))
     }
 }
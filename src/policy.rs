@@ -0,0 +1,641 @@
+use std::cmp;
+
+use nodes::{List, Node, Slab};
+use {EvictionReason, InvariantError};
+
+// Decides which key an LFUCache evicts, see LFUCache::with_policy.
+//
+// The cache identifies each key by a handle, the index of its node,
+// which is valid from `on_insert` until `on_remove`. The policy keeps
+// every key it was told about in an eviction order: the first key is
+// the next victim and the last key is evicted last. The order is also
+// the order the cache iterates in.
+pub trait EvictionPolicy {
+    // A new key was inserted
+    fn on_insert(&mut self, node: usize);
+
+    // A key was looked up, or its value replaced
+    fn on_access(&mut self, node: usize);
+
+    // A key left the cache for the given reason
+    fn on_remove(&mut self, node: usize, reason: EvictionReason);
+
+    // The key that would be evicted next
+    fn choose_victim(&self) -> Option<usize>;
+
+    // The key that would be evicted last
+    fn last(&self) -> Option<usize>;
+
+    // The key evicted after the given one
+    fn next(&self, node: usize) -> Option<usize>;
+
+    // The key evicted before the given one
+    fn prev(&self, node: usize) -> Option<usize>;
+
+    // The cache halved every key's frequency, see LFUCache::age.
+    // Policies that don't count uses can ignore this.
+    fn on_age(&mut self) {}
+
+    // Check the policy's internal structure, see LFUCache::validate.
+    // The cache already checks that the order it sees holds every key
+    // once; this is for anything else the policy relies on.
+    fn validate(&self) -> Result<(), InvariantError> {
+        Ok(())
+    }
+}
+
+// Which of several keys with the same priority LfuPolicy and
+// LfuDaPolicy evict first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TieBreaking {
+    // The key used or inserted least recently
+    LeastRecentlyUsed,
+    // The key used or inserted most recently. This is the default.
+    MostRecentlyUsed,
+    // The key inserted first, no matter when it was last used. Keys
+    // are kept in insertion order within each list, so moving a key
+    // walks the list it moves to, from its most recently inserted
+    // key: a use costs O(length of that list) instead of O(1).
+    FirstInFirstOut
+}
+
+impl Default for TieBreaking {
+    fn default() -> Self {
+        TieBreaking::MostRecentlyUsed
+    }
+}
+
+// A key's position in one of the lists of PriorityLists
+#[derive(Debug, Clone, Copy, Default)]
+struct PriorityLink {
+    parent: usize,
+    next: Option<usize>,
+    prev: Option<usize>
+}
+
+impl Node for PriorityLink {
+    fn get_next(&self) -> Option<usize> {
+        self.next
+    }
+    fn set_next(&mut self, new_next: Option<usize>) {
+        self.next = new_next
+    }
+    fn get_prev(&self) -> Option<usize> {
+        self.prev
+    }
+    fn set_prev(&mut self, new_prev: Option<usize>) {
+        self.prev = new_prev
+    }
+}
+
+// A linked list of keys with the same priority. This struct is also
+// itself a node in a linked list of PriorityList objects, ordered by
+// priority.
+#[derive(Debug)]
+struct PriorityList {
+    nodes: List,
+    priority: usize,
+    next: Option<usize>,
+    prev: Option<usize>
+}
+
+impl PriorityList {
+    fn new(priority: usize) -> Self {
+        PriorityList {
+            nodes: List::new(),
+            priority,
+            next: None, prev: None
+        }
+    }
+}
+
+impl Node for PriorityList {
+    fn get_next(&self) -> Option<usize> {
+        self.next
+    }
+    fn set_next(&mut self, new_next: Option<usize>) {
+        self.next = new_next
+    }
+    fn get_prev(&self) -> Option<usize> {
+        self.prev
+    }
+    fn set_prev(&mut self, new_prev: Option<usize>) {
+        self.prev = new_prev
+    }
+}
+
+// Keys grouped into lists by priority, from the lowest priority to the
+// highest. This is the structure described in the paper: when a key's
+// priority only ever goes up by one, every operation is O(1). Within a
+// list, keys are ordered by the tie-breaking rule.
+#[derive(Debug, Default)]
+struct PriorityLists {
+    lists: List,
+    slab: Slab<PriorityList>,
+    links: Vec<PriorityLink>,
+    tie_breaking: TieBreaking,
+    // The insertion number of each node, for FIFO tie-breaking
+    inserted: Vec<u64>,
+    insertions: u64
+}
+
+impl PriorityLists {
+    fn new(tie_breaking: TieBreaking) -> Self {
+        PriorityLists { tie_breaking, ..PriorityLists::default() }
+    }
+
+    fn priority(&self, node: usize) -> usize {
+        self.slab[self.links[node].parent].priority
+    }
+
+    // Link the given node into the given list, in front of the nodes
+    // it should be evicted before
+    fn attach(&mut self, node: usize, list: usize) {
+        match self.tie_breaking {
+            TieBreaking::MostRecentlyUsed => self.slab[list].nodes.push_front(&mut self.links, node),
+            TieBreaking::LeastRecentlyUsed => self.slab[list].nodes.push_back(&mut self.links, node),
+            TieBreaking::FirstInFirstOut => {
+                // walk back past the nodes inserted later
+                let mut after = self.slab[list].nodes.tail();
+                while let Some(prev) = after {
+                    if self.inserted[prev] < self.inserted[node] {
+                        break;
+                    }
+                    after = self.links[prev].prev;
+                }
+                match after {
+                    Some(after) => self.slab[list].nodes.insert_after(&mut self.links, after, node),
+                    None => self.slab[list].nodes.push_front(&mut self.links, node)
+                }
+            }
+        }
+        self.links[node].parent = list;
+    }
+
+    // Unlink the given node from its list, removing the list if this
+    // was its last node
+    fn detach(&mut self, node: usize) {
+        let parent = self.links[node].parent;
+        self.slab[parent].nodes.remove(&mut self.links, node);
+        if self.slab[parent].nodes.is_empty() {
+            self.lists.remove(&mut self.slab, parent);
+            self.slab.remove(parent);
+        }
+    }
+
+    // Find the list with the given priority, creating it if there is
+    // none. The search starts after the `start` list, whose priority
+    // must be lower, or at the first list. When the priority is one
+    // more than the start's, the list is always next to it, so this
+    // is O(1).
+    fn list_for_priority(&mut self, priority: usize, start: Option<usize>) -> usize {
+        let mut prev = start;
+        let mut next = match start {
+            Some(start) => self.slab[start].next,
+            None => self.lists.head()
+        };
+        while let Some(list) = next {
+            if self.slab[list].priority >= priority {
+                break;
+            }
+            prev = Some(list);
+            next = self.slab[list].next;
+        }
+
+        match next {
+            Some(list) if self.slab[list].priority == priority => list,
+            _ => {
+                let list = self.slab.insert(PriorityList::new(priority));
+                match prev {
+                    Some(prev) => self.lists.insert_after(&mut self.slab, prev, list),
+                    None => self.lists.push_front(&mut self.slab, list)
+                }
+                list
+            }
+        }
+    }
+
+    fn insert(&mut self, node: usize, priority: usize) {
+        if node >= self.links.len() {
+            self.links.resize(node + 1, PriorityLink::default());
+            self.inserted.resize(node + 1, 0);
+        }
+        self.inserted[node] = self.insertions;
+        self.insertions += 1;
+        let list = self.list_for_priority(priority, None);
+        self.attach(node, list);
+    }
+
+    // Move the given node to the list of its new priority, which must
+    // not be lower than its current one
+    fn raise(&mut self, node: usize, priority: usize) {
+        // The list before the current one has a lower priority and
+        // survives detaching the node, so the search can start there
+        let start = self.slab[self.links[node].parent].prev;
+        self.detach(node);
+        let list = self.list_for_priority(priority, start);
+        self.attach(node, list);
+    }
+
+    fn remove(&mut self, node: usize) {
+        self.detach(node);
+    }
+
+    fn first(&self) -> Option<usize> {
+        self.slab[self.lists.head()?].nodes.head()
+    }
+
+    fn last(&self) -> Option<usize> {
+        self.slab[self.lists.tail()?].nodes.tail()
+    }
+
+    // The node after the given one, moving on to the next list at the
+    // end of this one
+    fn next(&self, node: usize) -> Option<usize> {
+        let link = &self.links[node];
+        link.next.or_else(|| {
+            let list = self.slab[link.parent].next?;
+            self.slab[list].nodes.head()
+        })
+    }
+
+    fn prev(&self, node: usize) -> Option<usize> {
+        let link = &self.links[node];
+        link.prev.or_else(|| {
+            let list = self.slab[link.parent].prev?;
+            self.slab[list].nodes.tail()
+        })
+    }
+
+    // Halve every priority, rounding down but never below 1. Lists
+    // whose priorities become equal are merged, with the nodes that
+    // used to have the lower priority evicted first, unless ties are
+    // broken by insertion order.
+    fn halve(&mut self) {
+        let mut current = self.lists.head();
+        while let Some(list) = current {
+            current = self.slab[list].next;
+            let priority = cmp::max(1, self.slab[list].priority / 2);
+            self.slab[list].priority = priority;
+            if let Some(prev) = self.slab[list].prev {
+                if self.slab[prev].priority == priority {
+                    self.merge(prev, list);
+                }
+            }
+        }
+    }
+
+    // Check that the lists are in strictly increasing order of
+    // priority, that none is empty or unlinked, and that every node's
+    // parent is the list it is in. With FIFO tie-breaking, each list
+    // must also be in insertion order.
+    fn validate(&self) -> Result<(), InvariantError> {
+        let lists = self.lists.validate(|list| self.slab.get(list))?;
+        if lists.len() != self.slab.len() {
+            return Err(InvariantError::new(format!(
+                "{} of the {} priority lists are linked", lists.len(), self.slab.len())));
+        }
+        let mut previous_priority = None;
+        for list in lists {
+            let priority = self.slab[list].priority;
+            if previous_priority.map_or(false, |previous| previous >= priority) {
+                return Err(InvariantError::new(format!(
+                    "priority {} follows priority {:?}", priority, previous_priority)));
+            }
+            previous_priority = Some(priority);
+
+            let nodes = self.slab[list].nodes.validate(|node| self.links.get(node))?;
+            if nodes.is_empty() {
+                return Err(InvariantError::new(format!("the list of priority {} is empty", priority)));
+            }
+            for &node in &nodes {
+                if self.links[node].parent != list {
+                    return Err(InvariantError::new(format!(
+                        "node {} is in the list of priority {} but its parent is another list",
+                        node, priority)));
+                }
+            }
+            if self.tie_breaking == TieBreaking::FirstInFirstOut
+                && nodes.windows(2).any(|pair| self.inserted[pair[0]] > self.inserted[pair[1]]) {
+                return Err(InvariantError::new(format!(
+                    "the list of priority {} is not in insertion order", priority)));
+            }
+        }
+        Ok(())
+    }
+
+    // Move every node of `list` to the back of `into`, or into
+    // insertion order with FIFO tie-breaking, then remove `list`
+    fn merge(&mut self, into: usize, list: usize) {
+        while let Some(node) = self.slab[list].nodes.pop_front(&mut self.links) {
+            if self.tie_breaking == TieBreaking::FirstInFirstOut {
+                self.attach(node, into);
+            } else {
+                self.slab[into].nodes.push_back(&mut self.links, node);
+                self.links[node].parent = into;
+            }
+        }
+        self.lists.remove(&mut self.slab, list);
+        self.slab.remove(list);
+    }
+}
+
+// The default policy: evict the least frequently used key, in O(1)
+#[derive(Debug, Default)]
+pub struct LfuPolicy {
+    lists: PriorityLists
+}
+
+impl LfuPolicy {
+    pub fn new() -> Self {
+        LfuPolicy::default()
+    }
+
+    // Create a policy that breaks ties between equally frequently
+    // used keys by the given rule
+    pub fn with_tie_breaking(tie_breaking: TieBreaking) -> Self {
+        LfuPolicy { lists: PriorityLists::new(tie_breaking) }
+    }
+
+    pub fn tie_breaking(&self) -> TieBreaking {
+        self.lists.tie_breaking
+    }
+}
+
+impl EvictionPolicy for LfuPolicy {
+    fn on_insert(&mut self, node: usize) {
+        self.lists.insert(node, 1);
+    }
+
+    fn on_access(&mut self, node: usize) {
+        let frequency = self.lists.priority(node) + 1;
+        self.lists.raise(node, frequency);
+    }
+
+    fn on_remove(&mut self, node: usize, _: EvictionReason) {
+        self.lists.remove(node);
+    }
+
+    fn choose_victim(&self) -> Option<usize> {
+        self.lists.first()
+    }
+
+    fn last(&self) -> Option<usize> {
+        self.lists.last()
+    }
+
+    fn next(&self, node: usize) -> Option<usize> {
+        self.lists.next(node)
+    }
+
+    fn prev(&self, node: usize) -> Option<usize> {
+        self.lists.prev(node)
+    }
+
+    fn on_age(&mut self) {
+        self.lists.halve();
+    }
+
+    fn validate(&self) -> Result<(), InvariantError> {
+        self.lists.validate()
+    }
+}
+
+// LFU with dynamic aging (LFU-DA). Each key's priority is its
+// frequency plus the cache age at its last use, and the cache age
+// rises to the priority of each key evicted to make room. Keys that
+// were hot long ago are eventually displaced by keys that are hot now.
+//
+// A use can raise a key's priority by more than one, so unlike
+// LfuPolicy, moving it walks past the priorities in between.
+#[derive(Debug, Default)]
+pub struct LfuDaPolicy {
+    lists: PriorityLists,
+    frequencies: Vec<usize>,
+    age: usize
+}
+
+impl LfuDaPolicy {
+    pub fn new() -> Self {
+        LfuDaPolicy::default()
+    }
+
+    // Create a policy that breaks ties between keys of equal priority
+    // by the given rule
+    pub fn with_tie_breaking(tie_breaking: TieBreaking) -> Self {
+        LfuDaPolicy { lists: PriorityLists::new(tie_breaking), ..LfuDaPolicy::default() }
+    }
+
+    pub fn tie_breaking(&self) -> TieBreaking {
+        self.lists.tie_breaking
+    }
+
+    // The highest priority of the keys evicted to make room, halved
+    // whenever the cache ages
+    pub fn age(&self) -> usize {
+        self.age
+    }
+
+    fn priority(&self, node: usize) -> usize {
+        self.frequencies[node].saturating_add(self.age)
+    }
+}
+
+impl EvictionPolicy for LfuDaPolicy {
+    fn on_insert(&mut self, node: usize) {
+        if node >= self.frequencies.len() {
+            self.frequencies.resize(node + 1, 0);
+        }
+        self.frequencies[node] = 1;
+        let priority = self.priority(node);
+        self.lists.insert(node, priority);
+    }
+
+    fn on_access(&mut self, node: usize) {
+        self.frequencies[node] += 1;
+        let priority = self.priority(node);
+        self.lists.raise(node, priority);
+    }
+
+    fn on_remove(&mut self, node: usize, reason: EvictionReason) {
+        // The age never goes down, or keys used later could get a
+        // lower priority than they already have. A key that was
+        // skipped over can be evicted after one with a higher
+        // priority.
+        if reason == EvictionReason::Capacity {
+            self.age = cmp::max(self.age, self.lists.priority(node));
+        }
+        self.lists.remove(node);
+    }
+
+    fn choose_victim(&self) -> Option<usize> {
+        self.lists.first()
+    }
+
+    fn last(&self) -> Option<usize> {
+        self.lists.last()
+    }
+
+    fn next(&self, node: usize) -> Option<usize> {
+        self.lists.next(node)
+    }
+
+    fn prev(&self, node: usize) -> Option<usize> {
+        self.lists.prev(node)
+    }
+
+    // Priorities, frequencies and the cache age are all halved
+    fn on_age(&mut self) {
+        self.lists.halve();
+        self.age /= 2;
+        for frequency in &mut self.frequencies {
+            *frequency = cmp::max(1, *frequency / 2);
+        }
+    }
+
+    fn validate(&self) -> Result<(), InvariantError> {
+        self.lists.validate()
+    }
+}
+
+// A key's position in a Queue
+#[derive(Debug, Clone, Copy, Default)]
+struct QueueLink {
+    next: Option<usize>,
+    prev: Option<usize>
+}
+
+impl Node for QueueLink {
+    fn get_next(&self) -> Option<usize> {
+        self.next
+    }
+    fn set_next(&mut self, new_next: Option<usize>) {
+        self.next = new_next
+    }
+    fn get_prev(&self) -> Option<usize> {
+        self.prev
+    }
+    fn set_prev(&mut self, new_prev: Option<usize>) {
+        self.prev = new_prev
+    }
+}
+
+// Keys in a single list, evicted from the front
+#[derive(Debug, Default)]
+struct Queue {
+    list: List,
+    links: Vec<QueueLink>
+}
+
+impl Queue {
+    fn push_back(&mut self, node: usize) {
+        if node >= self.links.len() {
+            self.links.resize(node + 1, QueueLink::default());
+        }
+        self.list.push_back(&mut self.links, node);
+    }
+
+    fn move_to_back(&mut self, node: usize) {
+        self.list.remove(&mut self.links, node);
+        self.list.push_back(&mut self.links, node);
+    }
+
+    fn remove(&mut self, node: usize) {
+        self.list.remove(&mut self.links, node);
+    }
+
+    fn validate(&self) -> Result<(), InvariantError> {
+        self.list.validate(|node| self.links.get(node)).map(|_| ())
+    }
+}
+
+// Evict the least recently used key
+#[derive(Debug, Default)]
+pub struct LruPolicy {
+    queue: Queue
+}
+
+impl LruPolicy {
+    pub fn new() -> Self {
+        LruPolicy::default()
+    }
+}
+
+impl EvictionPolicy for LruPolicy {
+    fn on_insert(&mut self, node: usize) {
+        self.queue.push_back(node);
+    }
+
+    fn on_access(&mut self, node: usize) {
+        self.queue.move_to_back(node);
+    }
+
+    fn on_remove(&mut self, node: usize, _: EvictionReason) {
+        self.queue.remove(node);
+    }
+
+    fn choose_victim(&self) -> Option<usize> {
+        self.queue.list.head()
+    }
+
+    fn last(&self) -> Option<usize> {
+        self.queue.list.tail()
+    }
+
+    fn next(&self, node: usize) -> Option<usize> {
+        self.queue.links[node].next
+    }
+
+    fn prev(&self, node: usize) -> Option<usize> {
+        self.queue.links[node].prev
+    }
+
+    fn validate(&self) -> Result<(), InvariantError> {
+        self.queue.validate()
+    }
+}
+
+// Evict the key that was inserted first, no matter how often it is
+// used
+#[derive(Debug, Default)]
+pub struct FifoPolicy {
+    queue: Queue
+}
+
+impl FifoPolicy {
+    pub fn new() -> Self {
+        FifoPolicy::default()
+    }
+}
+
+impl EvictionPolicy for FifoPolicy {
+    fn on_insert(&mut self, node: usize) {
+        self.queue.push_back(node);
+    }
+
+    fn on_access(&mut self, _: usize) {}
+
+    fn on_remove(&mut self, node: usize, _: EvictionReason) {
+        self.queue.remove(node);
+    }
+
+    fn choose_victim(&self) -> Option<usize> {
+        self.queue.list.head()
+    }
+
+    fn last(&self) -> Option<usize> {
+        self.queue.list.tail()
+    }
+
+    fn next(&self, node: usize) -> Option<usize> {
+        self.queue.links[node].next
+    }
+
+    fn prev(&self, node: usize) -> Option<usize> {
+        self.queue.links[node].prev
+    }
+
+    fn validate(&self) -> Result<(), InvariantError> {
+        self.queue.validate()
+    }
+}
@@ -0,0 +1,151 @@
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::sync::{Mutex, MutexGuard};
+
+use LFUCache;
+
+// A snapshot of a single shard's size and eviction count
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShardStats {
+    pub len: usize,
+    pub capacity: usize,
+    pub evictions: usize
+}
+
+// A single LFUCache and the number of keys it has evicted
+#[derive(Debug)]
+struct Shard<K, V>
+where K: Hash + Eq {
+    cache: LFUCache<K, V>,
+    evictions: usize
+}
+
+// An LFU cache that can be shared between threads, split into a number
+// of independently locked LFUCache shards. Each key is hashed to a
+// single shard, so threads working on different shards don't contend
+// for the same lock. Eviction happens per shard: a full shard evicts
+// its own least frequently used key even if other shards have room.
+#[derive(Debug)]
+pub struct ShardedLFUCache<K, V>
+where K: Hash + Eq {
+    shards: Vec<Mutex<Shard<K, V>>>,
+    hash_builder: RandomState
+}
+
+impl<K, V> ShardedLFUCache<K, V>
+where K: Hash + Eq {
+    // Create a cache holding up to `max_size` keys in total, divided
+    // as evenly as possible across `shard_count` shards. Panics if
+    // `shard_count` is 0, or if `max_size` is less than `shard_count`,
+    // which would leave some shards unable to hold any key.
+    pub fn new(max_size: usize, shard_count: usize) -> Self {
+        assert!(shard_count > 0, "a sharded cache needs at least one shard");
+        assert_shards_fit(max_size, shard_count);
+
+        let shards = (0..shard_count).map(|i| {
+            Mutex::new(Shard {
+                cache: LFUCache::new(shard_capacity(max_size, shard_count, i)),
+                evictions: 0
+            })
+        }).collect();
+
+        ShardedLFUCache {
+            shards,
+            hash_builder: RandomState::new()
+        }
+    }
+
+    fn lock(&self, shard: usize) -> MutexGuard<'_, Shard<K, V>> {
+        self.shards[shard].lock().expect("a thread panicked while holding a shard lock")
+    }
+
+    fn shard_for<Q>(&self, key: &Q) -> usize
+    where Q: Hash + ?Sized {
+        let mut hasher = self.hash_builder.build_hasher();
+        key.hash(&mut hasher);
+        (hasher.finish() % self.shards.len() as u64) as usize
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    // Get the size, capacity and eviction count of every shard
+    pub fn shard_stats(&self) -> Vec<ShardStats> {
+        (0..self.shards.len()).map(|i| {
+            let shard = self.lock(i);
+            ShardStats {
+                len: shard.cache.len(),
+                capacity: shard.cache.capacity(),
+                evictions: shard.evictions
+            }
+        }).collect()
+    }
+
+    // The maximum number of keys across all shards
+    pub fn capacity(&self) -> usize {
+        (0..self.shards.len()).map(|i| self.lock(i).cache.capacity()).sum()
+    }
+
+    // Change the maximum number of keys, dividing it across the shards
+    // like `new` does. Shards that shrink evict their least frequently
+    // used keys to fit; all evicted keys are returned and counted in
+    // the shard stats. Panics if `max_size` is less than the number of
+    // shards.
+    pub fn set_capacity(&self, max_size: usize) -> Vec<(K, V)> {
+        let shard_count = self.shards.len();
+        assert_shards_fit(max_size, shard_count);
+        let mut evicted = Vec::new();
+        for i in 0..shard_count {
+            let mut shard = self.lock(i);
+            let shard_evicted = shard.cache.set_capacity(shard_capacity(max_size, shard_count, i));
+            shard.evictions += shard_evicted.len();
+            evicted.extend(shard_evicted);
+        }
+        evicted
+    }
+
+    pub fn len(&self) -> usize {
+        (0..self.shards.len()).map(|i| self.lock(i).cache.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..self.shards.len()).all(|i| self.lock(i).cache.is_empty())
+    }
+
+    // Get a clone of the value associated with the given key,
+    // incrementing the key's frequency within its shard.
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized, V: Clone {
+        self.lock(self.shard_for(key)).cache.get(key).cloned()
+    }
+
+    // Remove the value associated with the given key.
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        self.lock(self.shard_for(key)).cache.remove(key)
+    }
+
+    // Insert the value associated with the given key, evicting the
+    // least frequently used key of its shard if the shard is full.
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        let mut shard = self.lock(self.shard_for(&key));
+        let result = shard.cache.insert_with_eviction(key, value);
+        shard.evictions += result.evicted.len();
+        result.replaced
+    }
+}
+
+fn assert_shards_fit(max_size: usize, shard_count: usize) {
+    assert!(max_size >= shard_count,
+            "a capacity of {} leaves some of the {} shards without room for a key",
+            max_size, shard_count);
+}
+
+// The capacity of the given shard when `max_size` keys are divided
+// across `shard_count` shards. The remainder is handed out one key at
+// a time to the first shards.
+fn shard_capacity(max_size: usize, shard_count: usize, shard: usize) -> usize {
+    max_size / shard_count + if shard < max_size % shard_count { 1 } else { 0 }
+}
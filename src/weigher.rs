@@ -0,0 +1,13 @@
+// Assigns each entry of an LFUCache a weight, e.g. its size in bytes,
+// see LFUCache::with_weigher. The cache evicts least frequently used
+// keys until the total weight fits within its capacity.
+pub trait Weigher<K, V> {
+    fn weigh(&self, key: &K, value: &V) -> usize;
+}
+
+impl<K, V, F> Weigher<K, V> for F
+where F: Fn(&K, &V) -> usize {
+    fn weigh(&self, key: &K, value: &V) -> usize {
+        self(key, value)
+    }
+}
@@ -0,0 +1,148 @@
+extern crate lfu_rs;
+
+use lfu_rs::{EvictionReason, LFUCache, ManualClock, TinyLfu};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[test]
+fn estimate_and_reset() {
+    let mut filter = TinyLfu::with_sample_size(1024, 100);
+    assert_eq!(filter.sample_size(), 100);
+    assert_eq!(filter.estimate("key1"), 0);
+
+    for _ in 0..5 {
+        filter.record("key1");
+    }
+    filter.record("key2");
+    assert_eq!(filter.estimate("key1"), 5);
+    assert_eq!(filter.estimate("key2"), 1);
+    assert!(filter.admit("key1", "key2"));
+    assert!(!filter.admit("key2", "key1"));
+    assert!(!filter.admit("key3", "key2"));
+
+    // the doorkeeper is cleared and the sketch halved
+    filter.reset();
+    assert_eq!(filter.estimate("key1"), 2);
+    assert_eq!(filter.estimate("key2"), 0);
+}
+
+#[test]
+fn resets_after_sample_size() {
+    let mut filter = TinyLfu::with_sample_size(1024, 10);
+    for _ in 0..9 {
+        filter.record("key1");
+    }
+    assert_eq!(filter.estimate("key1"), 9);
+    filter.record("key1");
+    assert_eq!(filter.estimate("key1"), 4);
+}
+
+#[test]
+fn rejects_one_hit_wonders() {
+    let rejections = Arc::new(Mutex::new(Vec::new()));
+    let recorder = Arc::clone(&rejections);
+    let mut cache: LFUCache<String, String> = LFUCache::new(2);
+    cache.set_admission_filter(Some(TinyLfu::new(1024)));
+    cache.set_eviction_listener(move |key: &String, _: &String, _, reason| {
+        recorder.lock().unwrap().push((key.clone(), reason));
+    });
+
+    cache.insert("key1".to_string(), "val1".to_string());
+    cache.insert("key2".to_string(), "val2".to_string());
+    cache.get(&"key2".to_string());
+    cache.get(&"key2".to_string());
+    for _ in 0..5 {
+        cache.get(&"key1".to_string());
+    }
+
+    let result = cache.insert_with_eviction("key3".to_string(), "val3".to_string());
+    assert_eq!(result.evicted, vec![("key3".to_string(), "val3".to_string())]);
+    assert_eq!(*rejections.lock().unwrap(), vec![("key3".to_string(), EvictionReason::Rejected)]);
+    assert!(!cache.contains_key(&"key3".to_string()));
+    assert_eq!(cache.len(), 2);
+
+    // misses count too, so a key that keeps being asked for gets in
+    for _ in 0..3 {
+        assert_eq!(cache.get(&"key3".to_string()), None);
+    }
+    let result = cache.insert_with_eviction("key3".to_string(), "val3".to_string());
+    assert_eq!(result.evicted, vec![("key2".to_string(), "val2".to_string())]);
+    assert!(cache.contains_key(&"key3".to_string()));
+}
+
+#[test]
+fn admits_when_there_is_room() {
+    let mut cache: LFUCache<String, String> = LFUCache::new(2);
+    cache.set_admission_filter(Some(TinyLfu::new(1024)));
+    assert!(cache.admission_filter().is_some());
+    cache.insert("key1".to_string(), "val1".to_string());
+    cache.insert("key2".to_string(), "val2".to_string());
+    assert_eq!(cache.len(), 2);
+
+    // the entry API always admits
+    cache.get(&"key1".to_string());
+    cache.get(&"key2".to_string());
+    cache.entry("key3".to_string()).or_insert("val3".to_string());
+    assert!(cache.contains_key(&"key3".to_string()));
+
+    cache.set_admission_filter(None);
+    assert!(cache.admission_filter().is_none());
+}
+
+#[test]
+fn expired_entries_only_admit_what_they_make_room_for() {
+    let clock = ManualClock::new();
+    let mut cache = LFUCache::with_weigher(10, |_: &&str, value: &usize| *value);
+    cache.set_clock(clock.clone());
+    cache.set_admission_filter(Some(TinyLfu::new(1024)));
+    cache.insert_with_ttl("stale", 1, Duration::from_secs(1));
+    cache.insert("hot", 9);
+    for _ in 0..10 {
+        cache.get("hot");
+    }
+    clock.advance(Duration::from_secs(2));
+
+    // purging the stale entry frees too little, so the new key still
+    // has to beat the hot one
+    let result = cache.insert_with_eviction("new", 9);
+    assert_eq!(result.evicted, vec![("new", 9)]);
+    assert!(cache.contains_key("hot"));
+
+    // but it is admitted if the stale entry alone makes room
+    let result = cache.insert_with_eviction("new", 1);
+    assert_eq!(result.evicted, vec![("stale", 1)]);
+    assert!(cache.contains_key("hot"));
+    assert!(cache.contains_key("new"));
+}
+
+// Hot keys are looked up over and over, but between each lookup there
+// is a key that is never seen again
+#[test]
+fn improves_hit_ratio() {
+    fn run(cache: &mut LFUCache<usize, usize>) -> usize {
+        let mut hits = 0;
+        let mut one_off = 1000;
+        for round in 0..100 {
+            for hot in 0..8 {
+                for &key in &[hot, one_off] {
+                    if cache.get(&key).is_some() {
+                        if round > 0 {
+                            hits += 1;
+                        }
+                    } else {
+                        cache.insert(key, key);
+                    }
+                }
+                one_off += 1;
+            }
+        }
+        hits
+    }
+
+    let mut plain = LFUCache::new(8);
+    let mut filtered = LFUCache::new(8);
+    filtered.set_admission_filter(Some(TinyLfu::new(8)));
+    let plain_hits = run(&mut plain);
+    let filtered_hits = run(&mut filtered);
+    assert!(filtered_hits > plain_hits, "{} <= {}", filtered_hits, plain_hits);
+}
@@ -0,0 +1,111 @@
+extern crate lfu_rs;
+
+use lfu_rs::LFUCache;
+
+fn frequencies(cache: &LFUCache<String, String>) -> Vec<(String, usize)> {
+    cache.iter().map(|(key, _, frequency)| (key.clone(), frequency)).collect()
+}
+
+fn freq(key: &str, frequency: usize) -> (String, usize) {
+    (key.to_string(), frequency)
+}
+
+#[test]
+fn age_halves_frequencies() {
+    let mut cache: LFUCache<String, String> = LFUCache::new(10);
+    cache.insert("key1".to_string(), "val1".to_string());
+    cache.insert("key2".to_string(), "val2".to_string());
+    cache.insert("key3".to_string(), "val3".to_string());
+    for _ in 0..7 {
+        cache.get(&"key3".to_string());
+    }
+    for _ in 0..2 {
+        cache.get(&"key2".to_string());
+    }
+
+    assert_eq!(frequencies(&cache), vec![freq("key1", 1), freq("key2", 3), freq("key3", 8)]);
+    cache.age();
+    assert_eq!(frequencies(&cache), vec![freq("key1", 1), freq("key2", 1), freq("key3", 4)]);
+    cache.age();
+    assert_eq!(frequencies(&cache), vec![freq("key1", 1), freq("key2", 1), freq("key3", 2)]);
+    cache.age();
+    assert_eq!(frequencies(&cache), vec![freq("key1", 1), freq("key2", 1), freq("key3", 1)]);
+
+    // the lists were merged, so a use moves a key into a new list
+    cache.get(&"key1".to_string());
+    assert_eq!(frequencies(&cache), vec![freq("key2", 1), freq("key3", 1), freq("key1", 2)]);
+    assert_eq!(format!("{}", cache), "Count 1: val2 val3\nCount 2: val1\n");
+}
+
+#[test]
+fn merged_lists_evict_formerly_colder_keys_first() {
+    let mut cache: LFUCache<String, String> = LFUCache::new(2);
+    cache.insert("key1".to_string(), "val1".to_string());
+    cache.insert("key2".to_string(), "val2".to_string());
+    cache.get(&"key1".to_string());
+    cache.get(&"key1".to_string());
+    cache.get(&"key2".to_string());
+
+    cache.age();
+    assert_eq!(frequencies(&cache), vec![freq("key2", 1), freq("key1", 1)]);
+
+    cache.insert("key3".to_string(), "val3".to_string());
+    assert!(!cache.contains_key(&"key2".to_string()));
+    assert!(cache.contains_key(&"key1".to_string()));
+}
+
+#[test]
+fn aging_interval() {
+    let mut cache: LFUCache<String, String> = LFUCache::new(2);
+    assert_eq!(cache.aging_interval(), None);
+    cache.set_aging_interval(Some(10));
+    assert_eq!(cache.aging_interval(), Some(10));
+
+    // key1 was hot long ago
+    cache.insert("key1".to_string(), "val1".to_string());
+    for _ in 0..8 {
+        cache.get(&"key1".to_string());
+    }
+    assert_eq!(cache.frequency(&"key1".to_string()), Some(9));
+
+    // the tenth operation halves every frequency
+    cache.insert("key2".to_string(), "val2".to_string());
+    assert_eq!(cache.frequency(&"key1".to_string()), Some(4));
+    assert_eq!(cache.frequency(&"key2".to_string()), Some(1));
+
+    // key2 is hot now, and eventually outlives key1
+    for _ in 0..20 {
+        cache.get(&"key2".to_string());
+    }
+    cache.insert("key3".to_string(), "val3".to_string());
+    assert!(!cache.contains_key(&"key1".to_string()));
+    assert!(cache.contains_key(&"key2".to_string()));
+}
+
+#[test]
+fn without_aging_old_keys_stay() {
+    let mut cache: LFUCache<String, String> = LFUCache::new(2);
+    cache.insert("key1".to_string(), "val1".to_string());
+    for _ in 0..8 {
+        cache.get(&"key1".to_string());
+    }
+    cache.insert("key2".to_string(), "val2".to_string());
+    for _ in 0..7 {
+        cache.get(&"key2".to_string());
+    }
+    cache.insert("key3".to_string(), "val3".to_string());
+    assert!(cache.contains_key(&"key1".to_string()));
+    assert!(!cache.contains_key(&"key2".to_string()));
+}
+
+#[test]
+fn zero_aging_interval_disables_aging() {
+    let mut cache: LFUCache<String, String> = LFUCache::new(2);
+    cache.set_aging_interval(Some(0));
+    assert_eq!(cache.aging_interval(), None);
+
+    cache.insert("key1".to_string(), "val1".to_string());
+    cache.get(&"key1".to_string());
+    cache.get(&"key1".to_string());
+    assert_eq!(cache.frequency(&"key1".to_string()), Some(3));
+}
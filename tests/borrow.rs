@@ -0,0 +1,51 @@
+extern crate lfu_rs;
+
+use lfu_rs::{ConcurrentLFUCache, LFUCache, ShardedLFUCache};
+
+#[test]
+fn str_lookups_for_string_keys() {
+    let mut cache: LFUCache<String, String> = LFUCache::new(2);
+    cache.insert("key1".to_string(), "val1".to_string());
+    cache.insert("key2".to_string(), "val2".to_string());
+
+    assert_eq!(cache.get("key1"), Some(&"val1".to_string()));
+    cache.get_mut("key1").unwrap().push('!');
+    assert_eq!(cache.peek("key1"), Some(&"val1!".to_string()));
+    cache.peek_mut("key2").unwrap().push('?');
+    assert!(cache.contains_key("key2"));
+    assert_eq!(cache.frequency("key1"), Some(3));
+    assert_eq!(cache.remove("key2"), Some("val2?".to_string()));
+    assert!(!cache.contains_key("key2"));
+
+    // the owned key type still works
+    assert_eq!(cache.get(&"key1".to_string()), Some(&"val1!".to_string()));
+}
+
+#[test]
+fn slice_lookups_for_vec_keys() {
+    let mut cache: LFUCache<Vec<u8>, usize> = LFUCache::new(2);
+    cache.insert(vec![1, 2, 3], 6);
+    assert_eq!(cache.get(&[1, 2, 3][..]), Some(&6));
+    assert_eq!(cache.remove(&[1, 2, 3][..]), Some(6));
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn thread_safe_caches() {
+    let cache: ConcurrentLFUCache<String, String> = ConcurrentLFUCache::new(2);
+    cache.insert("key1".to_string(), "val1".to_string());
+    assert_eq!(cache.get("key1"), Some("val1".to_string()));
+    assert_eq!(cache.remove("key1"), Some("val1".to_string()));
+
+    // the shard is picked from the borrowed key's hash, which Borrow
+    // guarantees is the same as the owned key's
+    let cache: ShardedLFUCache<String, String> = ShardedLFUCache::new(32, 4);
+    for i in 0..8 {
+        cache.insert(format!("key{}", i), format!("val{}", i));
+    }
+    for i in 0..8 {
+        assert_eq!(cache.get(format!("key{}", i).as_str()), Some(format!("val{}", i)));
+    }
+    assert_eq!(cache.remove("key3"), Some("val3".to_string()));
+    assert_eq!(cache.len(), 7);
+}
@@ -0,0 +1,93 @@
+extern crate lfu_rs;
+
+use lfu_rs::{BuildError, EvictionReason, LFUCache, LFUCacheBuilder, LruPolicy, ManualClock, TieBreaking,
+             TinyLfu};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn keys(cache: &LFUCache<String, String>) -> Vec<&str> {
+    cache.keys().map(|key| key.as_str()).collect()
+}
+
+#[test]
+fn defaults() {
+    let mut cache: LFUCache<String, String> = LFUCache::builder().capacity(2).build().unwrap();
+    assert_eq!(cache.capacity(), 2);
+    assert_eq!(cache.default_ttl(), None);
+    assert_eq!(cache.aging_interval(), None);
+    assert!(cache.admission_filter().is_none());
+
+    cache.insert("key1".to_string(), "val1".to_string());
+    cache.insert("key2".to_string(), "val2".to_string());
+    cache.insert("key3".to_string(), "val3".to_string());
+    assert_eq!(keys(&cache), vec!["key3", "key1"]);
+}
+
+#[test]
+fn invalid_configurations() {
+    let build = |builder: LFUCacheBuilder<String, String>| builder.build().err();
+
+    assert_eq!(build(LFUCacheBuilder::new()), Some(BuildError::MissingCapacity));
+    assert_eq!(build(LFUCache::builder().capacity(0)), Some(BuildError::ZeroCapacity));
+    assert_eq!(build(LFUCache::builder().capacity(1).default_ttl(Duration::from_secs(0))),
+               Some(BuildError::ZeroTimeToLive));
+    assert_eq!(build(LFUCache::builder().capacity(1).aging_interval(0)),
+               Some(BuildError::ZeroAgingInterval));
+    assert_eq!(build(LFUCache::builder().capacity(1).policy(LruPolicy::new())
+                     .tie_breaking(TieBreaking::LeastRecentlyUsed)),
+               Some(BuildError::TieBreakingWithPolicy));
+
+    assert_eq!(BuildError::ZeroCapacity.to_string(),
+               "invalid cache configuration: the capacity must be greater than 0");
+}
+
+#[test]
+fn all_options() {
+    let evictions = Arc::new(Mutex::new(Vec::new()));
+    let recorder = Arc::clone(&evictions);
+    let clock = ManualClock::new();
+    let mut cache: LFUCache<String, String> = LFUCache::builder()
+        .capacity(6)
+        .initial_capacity(3)
+        .tie_breaking(TieBreaking::LeastRecentlyUsed)
+        .default_ttl(Duration::from_secs(10))
+        .clock(clock.clone())
+        .weigher(|_: &String, value: &String| value.len())
+        .eviction_listener(move |key: &String, _: &String, _, reason| {
+            recorder.lock().unwrap().push((key.clone(), reason));
+        })
+        .aging_interval(100)
+        .admission_filter(TinyLfu::new(3))
+        .build()
+        .unwrap();
+
+    assert_eq!(cache.capacity(), 6);
+    assert_eq!(cache.default_ttl(), Some(Duration::from_secs(10)));
+    assert_eq!(cache.aging_interval(), Some(100));
+    assert!(cache.admission_filter().is_some());
+
+    cache.insert("key1".to_string(), "aa".to_string());
+    cache.insert("key2".to_string(), "bb".to_string());
+    cache.insert("key3".to_string(), "cc".to_string());
+    assert_eq!(cache.weight(), 6);
+    assert_eq!(keys(&cache), vec!["key1", "key2", "key3"]);
+
+    clock.advance(Duration::from_secs(10));
+    assert_eq!(cache.get(&"key1".to_string()), None);
+    assert_eq!(*evictions.lock().unwrap(), vec![("key1".to_string(), EvictionReason::Expired)]);
+}
+
+#[test]
+fn custom_policy() {
+    let mut cache: LFUCache<String, String> = LFUCache::builder()
+        .capacity(2)
+        .policy(LruPolicy::new())
+        .build()
+        .unwrap();
+    cache.insert("key1".to_string(), "val1".to_string());
+    cache.insert("key2".to_string(), "val2".to_string());
+    cache.get(&"key2".to_string());
+    cache.get(&"key1".to_string());
+    cache.insert("key3".to_string(), "val3".to_string());
+    assert_eq!(keys(&cache), vec!["key1", "key3"]);
+}
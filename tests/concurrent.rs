@@ -0,0 +1,55 @@
+extern crate lfu_rs;
+
+use lfu_rs::ConcurrentLFUCache;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn insert_get_remove() {
+    let cache: ConcurrentLFUCache<String, String> = ConcurrentLFUCache::new(10);
+    assert_eq!(cache.insert("key1".to_string(), "val1".to_string()), None);
+    assert_eq!(cache.get(&"key1".to_string()), Some("val1".to_string()));
+    assert_eq!(cache.remove(&"key1".to_string()), Some("val1".to_string()));
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn shared_between_threads() {
+    let size = 100;
+    let cache: Arc<ConcurrentLFUCache<usize, usize>> = Arc::new(ConcurrentLFUCache::new(size));
+
+    let handles: Vec<_> = (0..4).map(|t| {
+        let cache = Arc::clone(&cache);
+        thread::spawn(move || {
+            for i in 0..1000 {
+                let key = t * 1000 + i;
+                cache.insert(key, key);
+                if let Some(value) = cache.get(&key) {
+                    assert_eq!(value, key);
+                }
+                if i % 2 == 0 {
+                    cache.remove(&key);
+                }
+            }
+        })
+    }).collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert!(cache.len() <= size);
+}
+
+#[test]
+fn evicts_least_frequently_used() {
+    let cache: ConcurrentLFUCache<usize, usize> = ConcurrentLFUCache::new(2);
+    cache.insert(1, 1);
+    cache.insert(2, 2);
+    cache.get(&1);
+    cache.insert(3, 3);
+
+    assert_eq!(cache.get(&1), Some(1));
+    assert_eq!(cache.get(&2), None);
+    assert_eq!(cache.get(&3), Some(3));
+}
@@ -0,0 +1,189 @@
+extern crate lfu_rs;
+
+use lfu_rs::{FifoPolicy, LFUCache, LfuDaPolicy, LruPolicy, ManualClock, TinyLfu};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+// A key or value that counts how many of its kind are alive
+#[derive(Debug)]
+struct Tracked {
+    id: usize,
+    live: Arc<AtomicUsize>
+}
+
+impl Tracked {
+    fn new(id: usize, live: &Arc<AtomicUsize>) -> Self {
+        live.fetch_add(1, Ordering::SeqCst);
+        Tracked { id, live: Arc::clone(live) }
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.live.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl PartialEq for Tracked {
+    fn eq(&self, other: &Tracked) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Tracked {}
+
+impl Hash for Tracked {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state)
+    }
+}
+
+struct Counts {
+    keys: Arc<AtomicUsize>,
+    values: Arc<AtomicUsize>
+}
+
+impl Counts {
+    fn new() -> Self {
+        Counts { keys: Arc::new(AtomicUsize::new(0)), values: Arc::new(AtomicUsize::new(0)) }
+    }
+
+    fn key(&self, id: usize) -> Tracked {
+        Tracked::new(id, &self.keys)
+    }
+
+    fn value(&self, id: usize) -> Tracked {
+        Tracked::new(id, &self.values)
+    }
+
+    fn insert(&self, cache: &mut LFUCache<Tracked, Tracked>, id: usize) {
+        cache.insert(self.key(id), self.value(id));
+    }
+
+    // Assert that exactly `len` keys and values are alive
+    fn assert_live(&self, len: usize) {
+        assert_eq!(self.keys.load(Ordering::SeqCst), len);
+        assert_eq!(self.values.load(Ordering::SeqCst), len);
+    }
+}
+
+#[test]
+fn insert_get_remove() {
+    let counts = Counts::new();
+    let mut cache = LFUCache::new(10);
+    for id in 0..5 {
+        counts.insert(&mut cache, id);
+    }
+    counts.assert_live(5);
+
+    for id in 0..5 {
+        cache.get(&counts.key(id));
+    }
+    counts.assert_live(5);
+
+    // replacing a value drops the old value and the duplicate key
+    counts.insert(&mut cache, 0);
+    counts.assert_live(5);
+
+    assert!(cache.remove(&counts.key(1)).is_some());
+    counts.assert_live(4);
+    assert!(cache.pop_lfu().is_some());
+    assert!(cache.pop_mfu().is_some());
+    counts.assert_live(2);
+
+    drop(cache);
+    counts.assert_live(0);
+}
+
+#[test]
+fn eviction() {
+    let counts = Counts::new();
+    let mut cache = LFUCache::new(3);
+    for id in 0..100 {
+        counts.insert(&mut cache, id);
+        cache.get(&counts.key(id % 7));
+        counts.assert_live(cache.len());
+    }
+    assert_eq!(cache.len(), 3);
+
+    cache.set_capacity(1);
+    counts.assert_live(1);
+    drop(cache);
+    counts.assert_live(0);
+}
+
+#[test]
+fn policies() {
+    let caches: Vec<LFUCache<Tracked, Tracked>> = vec![
+        LFUCache::with_policy(4, LfuDaPolicy::new()),
+        LFUCache::with_policy(4, LruPolicy::new()),
+        LFUCache::with_policy(4, FifoPolicy::new())
+    ];
+    for mut cache in caches {
+        let counts = Counts::new();
+        for id in 0..50 {
+            counts.insert(&mut cache, id);
+            cache.get(&counts.key(id / 2));
+            if id % 5 == 4 {
+                cache.remove(&counts.key(id - 1));
+            }
+            counts.assert_live(cache.len());
+        }
+        drop(cache);
+        counts.assert_live(0);
+    }
+}
+
+#[test]
+fn expiry_and_rejection() {
+    let counts = Counts::new();
+    let clock = ManualClock::new();
+    let mut cache = LFUCache::new(2);
+    cache.set_clock(clock.clone());
+    cache.insert_with_ttl(counts.key(0), counts.value(0), Duration::from_secs(1));
+    counts.insert(&mut cache, 1);
+    clock.advance(Duration::from_secs(2));
+    assert_eq!(cache.purge_expired().len(), 1);
+    counts.assert_live(1);
+
+    cache.set_admission_filter(Some(TinyLfu::new(2)));
+    counts.insert(&mut cache, 2);
+    for id in 3..10 {
+        // rejected entries are dropped along with the insert result
+        counts.insert(&mut cache, id);
+        counts.assert_live(2);
+    }
+}
+
+#[test]
+fn entry_api() {
+    let counts = Counts::new();
+    let mut cache = LFUCache::new(2);
+    cache.entry(counts.key(0)).or_insert_with(|| counts.value(0));
+    // an occupied entry drops the key it was looked up with
+    cache.entry(counts.key(0)).or_insert_with(|| counts.value(0));
+    counts.assert_live(1);
+    cache.entry(counts.key(1)).or_insert_with(|| counts.value(1));
+    cache.entry(counts.key(2)).or_insert_with(|| counts.value(2));
+    counts.assert_live(2);
+    drop(cache);
+    counts.assert_live(0);
+}
+
+#[test]
+fn partially_consumed_iterator() {
+    let counts = Counts::new();
+    let mut cache = LFUCache::new(5);
+    for id in 0..5 {
+        counts.insert(&mut cache, id);
+    }
+    let mut entries = cache.into_iter();
+    let first = entries.next();
+    counts.assert_live(5);
+    drop(entries);
+    counts.assert_live(1);
+    drop(first);
+    counts.assert_live(0);
+}
@@ -0,0 +1,188 @@
+extern crate lfu_rs;
+
+use lfu_rs::{EvictionPolicy, EvictionReason, LFUCache, LfuDaPolicy};
+use std::sync::{Arc, Mutex};
+
+// Look every key up once, inserting the ones that are missing.
+// Returns the number of hits.
+fn run(cache: &mut LFUCache<usize, usize>, keys: &[usize]) -> usize {
+    let mut hits = 0;
+    for &key in keys {
+        if cache.get(&key).is_some() {
+            hits += 1;
+        } else {
+            cache.insert(key, key);
+        }
+    }
+    hits
+}
+
+// 50 rounds over 0..5, then 50 rounds over 100..105
+fn shifting_workload() -> (Vec<usize>, Vec<usize>) {
+    let old: Vec<usize> = (0..50).flat_map(|_| 0..5).collect();
+    let new: Vec<usize> = (0..50).flat_map(|_| 100..105).collect();
+    (old, new)
+}
+
+fn keys(cache: &LFUCache<String, String>) -> Vec<&str> {
+    cache.keys().map(|key| key.as_str()).collect()
+}
+
+#[test]
+fn outperforms_lfu_on_shifting_workload() {
+    let (old, new) = shifting_workload();
+
+    let mut lfu = LFUCache::new(5);
+    run(&mut lfu, &old);
+    let lfu_hits = run(&mut lfu, &new);
+
+    let mut lfu_da = LFUCache::with_policy(5, LfuDaPolicy::new());
+    run(&mut lfu_da, &old);
+    let lfu_da_hits = run(&mut lfu_da, &new);
+
+    // plain LFU keeps the old keys forever, so the new keys keep
+    // evicting each other
+    assert_eq!(lfu_hits, 0);
+    assert!(lfu_da_hits > new.len() / 2, "{} hits", lfu_da_hits);
+    assert!((100..105).all(|key| lfu_da.contains_key(&key)));
+}
+
+#[test]
+fn eviction_raises_priority_of_new_keys() {
+    let mut cache: LFUCache<String, String> = LFUCache::with_policy(2, LfuDaPolicy::new());
+    cache.insert("key1".to_string(), "val1".to_string());
+    cache.insert("key2".to_string(), "val2".to_string());
+    cache.get(&"key1".to_string());
+    cache.get(&"key1".to_string());
+    cache.get(&"key2".to_string());
+    assert_eq!(keys(&cache), vec!["key2", "key1"]);
+
+    // key2 is evicted with priority 2, which becomes the cache age, so
+    // key3 starts out with priority 3 like key1 and is evicted first
+    cache.insert("key3".to_string(), "val3".to_string());
+    assert_eq!(keys(&cache), vec!["key3", "key1"]);
+
+    // frequencies are still reported without the age
+    assert_eq!(cache.frequency(&"key3".to_string()), Some(1));
+    assert_eq!(cache.frequency(&"key1".to_string()), Some(3));
+
+    // a use of key3 moves it past key1
+    cache.get(&"key3".to_string());
+    assert_eq!(keys(&cache), vec!["key1", "key3"]);
+    cache.insert("key4".to_string(), "val4".to_string());
+    assert_eq!(keys(&cache), vec!["key4", "key3"]);
+}
+
+#[test]
+fn explicit_removal_does_not_age() {
+    let mut cache: LFUCache<String, String> = LFUCache::with_policy(2, LfuDaPolicy::new());
+    cache.insert("key1".to_string(), "val1".to_string());
+    cache.get(&"key1".to_string());
+    cache.get(&"key1".to_string());
+    cache.remove(&"key1".to_string());
+    cache.insert("key2".to_string(), "val2".to_string());
+    cache.insert("key3".to_string(), "val3".to_string());
+    cache.get(&"key2".to_string());
+
+    // with an age of 0, key3 is still the least frequently used
+    cache.insert("key4".to_string(), "val4".to_string());
+    assert_eq!(keys(&cache), vec!["key4", "key2"]);
+}
+
+// An LfuDaPolicy that can still be inspected after it is handed to a
+// cache
+#[derive(Clone, Default)]
+struct SharedPolicy(Arc<Mutex<LfuDaPolicy>>);
+
+impl SharedPolicy {
+    fn age(&self) -> usize {
+        self.0.lock().unwrap().age()
+    }
+}
+
+impl EvictionPolicy for SharedPolicy {
+    fn on_insert(&mut self, node: usize) {
+        self.0.lock().unwrap().on_insert(node)
+    }
+
+    fn on_access(&mut self, node: usize) {
+        self.0.lock().unwrap().on_access(node)
+    }
+
+    fn on_remove(&mut self, node: usize, reason: EvictionReason) {
+        self.0.lock().unwrap().on_remove(node, reason)
+    }
+
+    fn choose_victim(&self) -> Option<usize> {
+        self.0.lock().unwrap().choose_victim()
+    }
+
+    fn last(&self) -> Option<usize> {
+        self.0.lock().unwrap().last()
+    }
+
+    fn next(&self, node: usize) -> Option<usize> {
+        self.0.lock().unwrap().next(node)
+    }
+
+    fn prev(&self, node: usize) -> Option<usize> {
+        self.0.lock().unwrap().prev(node)
+    }
+}
+
+#[test]
+fn oversized_insert_does_not_age() {
+    let policy = SharedPolicy::default();
+    let mut cache = LFUCache::builder()
+        .capacity(10)
+        .weigher(|_: &usize, value: &usize| *value)
+        .policy(policy.clone())
+        .build()
+        .unwrap();
+    cache.insert(1, 5);
+    cache.get(&1);
+
+    // neither a new key nor a replaced value that can't fit were
+    // evicted to make room
+    for key in 2..52 {
+        cache.insert(key, 11);
+    }
+    cache.insert(1, 11);
+    assert_eq!(policy.age(), 0);
+    assert!(cache.is_empty());
+
+    cache.insert(2, 6);
+    cache.insert(3, 6);
+    assert_eq!(policy.age(), 1);
+}
+
+// Replacing a value skips the key being replaced when making room, so
+// a key can be evicted after one with a higher priority. That must not
+// lower the cache age again.
+#[test]
+fn age_never_decreases() {
+    let policy = SharedPolicy::default();
+    let mut cache = LFUCache::builder()
+        .capacity(5)
+        .weigher(|_: &&str, value: &usize| *value)
+        .policy(policy.clone())
+        .build()
+        .unwrap();
+    for &key in &["a", "c", "f", "e", "b"] {
+        cache.insert(key, 1);
+    }
+    for &(key, uses) in &[("c", 8), ("f", 9), ("e", 10), ("b", 29)] {
+        for _ in 0..uses {
+            cache.get(key);
+        }
+    }
+
+    cache.insert("a", 2);
+    cache.get("f");
+    cache.get("e");
+    let age = policy.age();
+    cache.insert("d", 1);
+    assert!(policy.age() >= age);
+    cache.get("e");
+    cache.check_invariants();
+}
@@ -0,0 +1,73 @@
+extern crate lfu_rs;
+
+use lfu_rs::{Entry, LFUCache};
+
+#[test]
+fn or_insert_vacant() {
+    let mut cache: LFUCache<String, String> = LFUCache::new(10);
+
+    assert_eq!(cache.entry("key1".to_string()).or_insert("val1".to_string()), "val1");
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.to_string(), "Count 1: val1\n");
+}
+
+#[test]
+fn or_insert_occupied_counts_one_use() {
+    let mut cache: LFUCache<String, String> = LFUCache::new(10);
+    cache.insert("key1".to_string(), "val1".to_string());
+
+    assert_eq!(cache.entry("key1".to_string()).or_insert("other".to_string()), "val1");
+    assert_eq!(cache.to_string(), "Count 2: val1\n");
+
+    cache.entry("key1".to_string()).or_insert_with(|| panic!("key is occupied"));
+    assert_eq!(cache.to_string(), "Count 3: val1\n");
+}
+
+#[test]
+fn and_modify() {
+    let mut cache: LFUCache<String, usize> = LFUCache::new(10);
+
+    for _ in 0..3 {
+        cache.entry("hits".to_string()).and_modify(|v| *v += 1).or_insert(1);
+    }
+
+    assert_eq!(cache.to_string(), "Count 3: 3\n");
+}
+
+#[test]
+fn vacant_insert_evicts() {
+    let mut cache: LFUCache<String, String> = LFUCache::new(2);
+    cache.insert("key1".to_string(), "val1".to_string());
+    cache.insert("key2".to_string(), "val2".to_string());
+    cache.get(&"key1".to_string());
+
+    match cache.entry("key3".to_string()) {
+        Entry::Occupied(_) => panic!("key3 should be vacant"),
+        Entry::Vacant(entry) => {
+            assert_eq!(entry.key(), "key3");
+            entry.insert("val3".to_string());
+        }
+    }
+
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.to_string(), "Count 1: val3\nCount 2: val1\n");
+}
+
+#[test]
+fn occupied_remove() {
+    let mut cache: LFUCache<String, String> = LFUCache::new(10);
+    cache.insert("key1".to_string(), "val1".to_string());
+    cache.insert("key2".to_string(), "val2".to_string());
+
+    match cache.entry("key1".to_string()) {
+        Entry::Occupied(mut entry) => {
+            assert_eq!(entry.insert("new1".to_string()), "val1");
+            assert_eq!(entry.remove_entry(), ("key1".to_string(), "new1".to_string()));
+        }
+        Entry::Vacant(_) => panic!("key1 should be occupied"),
+    }
+
+    assert_eq!(cache.len(), 1);
+    assert!(cache.get(&"key1".to_string()).is_none());
+    assert_eq!(cache.to_string(), "Count 1: val2\n");
+}
@@ -0,0 +1,119 @@
+extern crate lfu_rs;
+
+use lfu_rs::{ConcurrentLFUCache, Entry, EvictionReason, InsertResult, LFUCache};
+use std::sync::{Arc, Mutex};
+
+type Evictions = Arc<Mutex<Vec<(String, String, usize, EvictionReason)>>>;
+
+fn recording_cache(max_size: usize) -> (LFUCache<String, String>, Evictions) {
+    let evictions: Evictions = Arc::new(Mutex::new(Vec::new()));
+    let mut cache = LFUCache::new(max_size);
+    let recorder = Arc::clone(&evictions);
+    cache.set_eviction_listener(move |key: &String, value: &String, frequency, reason| {
+        recorder.lock().unwrap().push((key.clone(), value.clone(), frequency, reason));
+    });
+    (cache, evictions)
+}
+
+fn eviction(key: &str, value: &str, frequency: usize, reason: EvictionReason)
+            -> (String, String, usize, EvictionReason) {
+    (key.to_string(), value.to_string(), frequency, reason)
+}
+
+#[test]
+fn capacity() {
+    let (mut cache, evictions) = recording_cache(2);
+    cache.insert("key1".to_string(), "val1".to_string());
+    cache.insert("key2".to_string(), "val2".to_string());
+    cache.get(&"key1".to_string());
+    cache.get(&"key2".to_string());
+    cache.get(&"key2".to_string());
+    cache.insert("key3".to_string(), "val3".to_string());
+
+    assert_eq!(*evictions.lock().unwrap(), vec![
+        eviction("key1", "val1", 2, EvictionReason::Capacity)
+    ]);
+}
+
+#[test]
+fn removed_and_replaced() {
+    let (mut cache, evictions) = recording_cache(10);
+    cache.insert("key1".to_string(), "val1".to_string());
+    cache.insert("key2".to_string(), "val2".to_string());
+
+    cache.insert("key1".to_string(), "new1".to_string());
+    cache.remove(&"key2".to_string());
+    cache.remove(&"missing".to_string());
+
+    assert_eq!(*evictions.lock().unwrap(), vec![
+        eviction("key1", "val1", 2, EvictionReason::Replaced),
+        eviction("key2", "val2", 1, EvictionReason::Removed),
+    ]);
+}
+
+#[test]
+fn through_entries() {
+    let (mut cache, evictions) = recording_cache(10);
+    cache.insert("key1".to_string(), "val1".to_string());
+
+    if let Entry::Occupied(mut entry) = cache.entry("key1".to_string()) {
+        entry.insert("new1".to_string());
+        entry.remove();
+    }
+
+    assert_eq!(*evictions.lock().unwrap(), vec![
+        eviction("key1", "val1", 2, EvictionReason::Replaced),
+        eviction("key1", "new1", 2, EvictionReason::Removed),
+    ]);
+}
+
+#[test]
+fn remove_listener() {
+    let (mut cache, evictions) = recording_cache(10);
+    cache.remove_eviction_listener();
+    cache.insert("key1".to_string(), "val1".to_string());
+    cache.remove(&"key1".to_string());
+
+    assert!(evictions.lock().unwrap().is_empty());
+}
+
+#[test]
+fn concurrent_cache() {
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let cache: ConcurrentLFUCache<usize, usize> = ConcurrentLFUCache::new(1);
+    let recorder = Arc::clone(&evicted);
+    cache.set_eviction_listener(move |key: &usize, _: &usize, _, _| {
+        recorder.lock().unwrap().push(*key);
+    });
+
+    cache.insert(1, 1);
+    cache.insert(2, 2);
+
+    assert_eq!(*evicted.lock().unwrap(), vec![1]);
+}
+
+#[test]
+fn insert_with_eviction() {
+    let mut cache: LFUCache<String, String> = LFUCache::new(2);
+
+    assert_eq!(
+        cache.insert_with_eviction("key1".to_string(), "val1".to_string()),
+        InsertResult { replaced: None, evicted: vec![] }
+    );
+    assert_eq!(
+        cache.insert_with_eviction("key2".to_string(), "val2".to_string()),
+        InsertResult { replaced: None, evicted: vec![] }
+    );
+    assert_eq!(
+        cache.insert_with_eviction("key1".to_string(), "new1".to_string()),
+        InsertResult { replaced: Some("val1".to_string()), evicted: vec![] }
+    );
+    assert_eq!(
+        cache.insert_with_eviction("key3".to_string(), "val3".to_string()),
+        InsertResult {
+            replaced: None,
+            evicted: vec![("key2".to_string(), "val2".to_string())]
+        }
+    );
+    assert_eq!(cache.len(), 2);
+}
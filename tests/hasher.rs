@@ -0,0 +1,71 @@
+extern crate lfu_rs;
+
+use lfu_rs::{Entry, LFUCache};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{BuildHasherDefault, Hasher};
+
+// Hashes integer keys to themselves
+#[derive(Default)]
+struct IdentityHasher(u64);
+
+impl Hasher for IdentityHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 << 8) | u64::from(byte);
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.0 = value;
+    }
+}
+
+type BuildIdentityHasher = BuildHasherDefault<IdentityHasher>;
+
+#[test]
+fn with_hasher() {
+    let mut cache: LFUCache<u64, &str, BuildIdentityHasher> =
+        LFUCache::with_hasher(2, BuildIdentityHasher::default());
+    cache.insert(1, "val1");
+    cache.insert(2, "val2");
+    cache.get(&1);
+    cache.insert(3, "val3");
+
+    assert_eq!(cache.get(&1), Some(&"val1"));
+    assert_eq!(cache.get(&2), None);
+    assert_eq!(cache.keys().cloned().collect::<Vec<_>>(), vec![3, 1]);
+    assert_eq!(format!("{}", cache), "Count 1: val3\nCount 3: val1\n");
+}
+
+#[test]
+fn with_capacity_and_hasher() {
+    let mut cache: LFUCache<String, String, BuildHasherDefault<DefaultHasher>> =
+        LFUCache::with_capacity_and_hasher(10, 10, BuildHasherDefault::default());
+    assert_eq!(cache.capacity(), 10);
+
+    match cache.entry("key1".to_string()) {
+        Entry::Vacant(entry) => { entry.insert("val1".to_string()); }
+        Entry::Occupied(_) => panic!("the cache is empty")
+    }
+    *cache.entry("key1".to_string()).or_default() += "!";
+    assert_eq!(cache.peek(&"key1".to_string()), Some(&"val1!".to_string()));
+    let _: &BuildHasherDefault<DefaultHasher> = cache.hasher();
+}
+
+#[test]
+fn builder() {
+    let mut cache = LFUCache::builder()
+        .capacity(2)
+        .hasher(BuildIdentityHasher::default())
+        .initial_capacity(2)
+        .build()
+        .unwrap();
+    cache.insert(1u64, "val1");
+    cache.insert(2, "val2");
+    cache.insert(3, "val3");
+    assert_eq!(cache.into_iter().map(|(key, _, _)| key).collect::<Vec<_>>(), vec![3, 1]);
+}
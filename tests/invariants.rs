@@ -0,0 +1,114 @@
+extern crate lfu_rs;
+
+use lfu_rs::{EvictionPolicy, EvictionReason, FifoPolicy, LFUCache, LfuDaPolicy, LfuPolicy,
+             LruPolicy, ManualClock, TieBreaking, TinyLfu};
+use std::time::Duration;
+
+// Run a mix of every kind of operation, validating the cache after
+// each one
+fn exercise(mut cache: LFUCache<usize, usize>) {
+    let clock = ManualClock::new();
+    cache.set_clock(clock.clone());
+    cache.set_aging_interval(Some(17));
+    for i in 0..200 {
+        match i % 7 {
+            0 => { cache.insert_with_ttl(i % 13, i, Duration::from_secs(3)); }
+            1 => { cache.get(&(i % 11)); }
+            2 => { cache.remove(&(i % 5)); }
+            3 => { *cache.entry(i % 9).or_insert(0) += 1; }
+            4 => { cache.pop_lfu(); }
+            5 => { clock.advance(Duration::from_secs(1)); cache.purge_expired(); }
+            _ => { cache.insert(i, i); }
+        }
+        cache.validate().unwrap();
+    }
+    cache.set_capacity(2);
+    cache.check_invariants();
+}
+
+#[test]
+fn policies_keep_their_invariants() {
+    for &tie_breaking in &[TieBreaking::LeastRecentlyUsed, TieBreaking::MostRecentlyUsed,
+                           TieBreaking::FirstInFirstOut] {
+        exercise(LFUCache::with_policy(8, LfuPolicy::with_tie_breaking(tie_breaking)));
+        exercise(LFUCache::with_policy(8, LfuDaPolicy::with_tie_breaking(tie_breaking)));
+    }
+    exercise(LFUCache::with_policy(8, LruPolicy::new()));
+    exercise(LFUCache::with_policy(8, FifoPolicy::new()));
+}
+
+#[test]
+fn weighed_and_filtered_caches() {
+    let mut cache = LFUCache::with_weigher(20, |_: &usize, value: &usize| value % 6 + 1);
+    cache.set_admission_filter(Some(TinyLfu::new(8)));
+    exercise(cache);
+}
+
+// Forgets every other key it is told about
+#[derive(Default)]
+struct Forgetful {
+    policy: LruPolicy,
+    inserted: usize
+}
+
+impl EvictionPolicy for Forgetful {
+    fn on_insert(&mut self, node: usize) {
+        self.inserted += 1;
+        if self.inserted % 2 == 1 {
+            self.policy.on_insert(node);
+        }
+    }
+
+    fn on_access(&mut self, _: usize) {}
+
+    fn on_remove(&mut self, node: usize, reason: EvictionReason) {
+        self.policy.on_remove(node, reason);
+    }
+
+    fn choose_victim(&self) -> Option<usize> {
+        self.policy.choose_victim()
+    }
+
+    fn last(&self) -> Option<usize> {
+        self.policy.last()
+    }
+
+    fn next(&self, node: usize) -> Option<usize> {
+        self.policy.next(node)
+    }
+
+    fn prev(&self, node: usize) -> Option<usize> {
+        self.policy.prev(node)
+    }
+}
+
+#[cfg(not(feature = "debug-invariants"))]
+#[test]
+fn broken_policy() {
+    let mut cache = LFUCache::with_policy(5, Forgetful::default());
+    cache.insert(1, 1);
+    assert_eq!(cache.validate(), Ok(()));
+    cache.insert(2, 2);
+    let error = cache.validate().unwrap_err();
+    assert_eq!(error.message(), "the eviction order holds 1 of the 2 nodes");
+    assert_eq!(error.to_string(), "cache invariant violated: the eviction order holds 1 of the 2 nodes");
+}
+
+#[cfg(not(feature = "debug-invariants"))]
+#[test]
+#[should_panic(expected = "cache invariant violated")]
+fn check_invariants_panics() {
+    let mut cache = LFUCache::with_policy(5, Forgetful::default());
+    cache.insert(1, 1);
+    cache.insert(2, 2);
+    cache.check_invariants();
+}
+
+#[cfg(feature = "debug-invariants")]
+#[test]
+#[should_panic(expected = "cache invariant violated")]
+fn broken_policy_is_caught_on_insert() {
+    let mut cache = LFUCache::with_policy(5, Forgetful::default());
+    cache.insert(1, 1);
+    cache.insert(2, 2);
+}
@@ -0,0 +1,92 @@
+extern crate lfu_rs;
+
+use lfu_rs::LFUCache;
+
+// key0 and key1 used once, key2 twice, key3 three times
+fn populated_cache() -> LFUCache<String, usize> {
+    let mut cache = LFUCache::new(10);
+    for i in 0..4 {
+        cache.insert(format!("key{}", i), i);
+    }
+    cache.get(&"key2".to_string());
+    cache.get(&"key3".to_string());
+    cache.get(&"key3".to_string());
+    cache
+}
+
+#[test]
+fn iter_in_eviction_order() {
+    let cache = populated_cache();
+
+    let entries: Vec<(&String, &usize, usize)> = cache.iter().collect();
+    assert_eq!(entries, vec![
+        (&"key1".to_string(), &1, 1),
+        (&"key0".to_string(), &0, 1),
+        (&"key2".to_string(), &2, 2),
+        (&"key3".to_string(), &3, 3),
+    ]);
+    assert_eq!(cache.iter().len(), 4);
+
+    // iterating doesn't count as a use
+    assert_eq!(cache.frequency(&"key1".to_string()), Some(1));
+}
+
+#[test]
+fn rev() {
+    let cache = populated_cache();
+
+    let keys: Vec<&String> = cache.keys().rev().collect();
+    assert_eq!(keys, vec!["key3", "key2", "key0", "key1"]);
+
+    let values: Vec<&usize> = cache.values().rev().collect();
+    assert_eq!(values, vec![&3, &2, &0, &1]);
+}
+
+#[test]
+fn iterate_from_both_ends() {
+    let cache = populated_cache();
+    let mut iter = cache.keys();
+
+    assert_eq!(iter.next().unwrap(), "key1");
+    assert_eq!(iter.next_back().unwrap(), "key3");
+    assert_eq!(iter.next_back().unwrap(), "key2");
+    assert_eq!(iter.next().unwrap(), "key0");
+    assert!(iter.next().is_none());
+    assert!(iter.next_back().is_none());
+}
+
+#[test]
+fn iter_mut() {
+    let mut cache = populated_cache();
+
+    for (_, value, frequency) in cache.iter_mut() {
+        *value *= frequency;
+    }
+    for (_, value, _) in &mut cache {
+        *value += 1;
+    }
+
+    let values: Vec<&usize> = cache.values().collect();
+    assert_eq!(values, vec![&2, &1, &5, &10]);
+}
+
+#[test]
+fn into_iter() {
+    let cache = populated_cache();
+
+    let entries: Vec<(String, usize, usize)> = cache.into_iter().rev().collect();
+    assert_eq!(entries, vec![
+        ("key3".to_string(), 3, 3),
+        ("key2".to_string(), 2, 2),
+        ("key0".to_string(), 0, 1),
+        ("key1".to_string(), 1, 1),
+    ]);
+}
+
+#[test]
+fn empty() {
+    let mut cache: LFUCache<String, usize> = LFUCache::new(10);
+    assert!(cache.iter().next().is_none());
+    assert!(cache.iter_mut().next_back().is_none());
+    assert!(cache.into_iter().next().is_none());
+}
@@ -0,0 +1,62 @@
+extern crate lfu_rs;
+
+use lfu_rs::{ConcurrentLFUCache, LFUCache, ShardedLFUCache};
+use std::borrow::Borrow;
+
+// A key that can't be cloned, like an owned handle
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct Handle(String);
+
+impl Borrow<str> for Handle {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+fn handle(name: &str) -> Handle {
+    Handle(name.to_string())
+}
+
+#[test]
+fn keys_need_not_be_clone() {
+    let mut cache: LFUCache<Handle, usize> = LFUCache::new(2);
+    assert_eq!(cache.insert(handle("a"), 1), None);
+    assert_eq!(cache.insert(handle("a"), 2), Some(1));
+    cache.insert(handle("b"), 3);
+    assert_eq!(cache.get("a"), Some(&2));
+    assert_eq!(cache.get(&handle("b")), Some(&3));
+    cache.get("a");
+
+    let result = cache.insert_with_eviction(handle("c"), 4);
+    assert_eq!(result.evicted, vec![(handle("b"), 3)]);
+    assert_eq!(cache.pop_lfu(), Some((handle("c"), 4)));
+    assert_eq!(cache.remove("a"), Some(2));
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn keys_are_handed_back() {
+    let mut cache: LFUCache<Handle, usize> = LFUCache::new(3);
+    *cache.entry(handle("a")).or_insert(0) += 1;
+    cache.insert(handle("b"), 2);
+    cache.insert(handle("c"), 3);
+    cache.get("c");
+    cache.get("c");
+
+    assert_eq!(cache.set_capacity(2), vec![(handle("b"), 2)]);
+    assert_eq!(cache.keys().collect::<Vec<_>>(), vec![&handle("a"), &handle("c")]);
+    let entries: Vec<(Handle, usize, usize)> = cache.into_iter().collect();
+    assert_eq!(entries, vec![(handle("a"), 1, 1), (handle("c"), 3, 3)]);
+}
+
+#[test]
+fn thread_safe_caches() {
+    let cache: ConcurrentLFUCache<Handle, usize> = ConcurrentLFUCache::new(2);
+    cache.insert(handle("a"), 1);
+    assert_eq!(cache.get("a"), Some(1));
+    assert_eq!(cache.remove("a"), Some(1));
+
+    let cache: ShardedLFUCache<Handle, usize> = ShardedLFUCache::new(8, 2);
+    cache.insert(handle("a"), 1);
+    assert_eq!(cache.get("a"), Some(1));
+}
@@ -1,54 +1,39 @@
 extern crate lfu_rs;
 
-use lfu_rs::nodes::{HasHead, Node};
-use std::rc::{Rc, Weak};
-use std::cell::RefCell;
+use lfu_rs::nodes::{List, Node, Slab};
 
 #[derive(Debug)]
 struct TestHead {
-    head: Option<Rc<RefCell<TestNode>>>
+    list: List,
+    slab: Slab<TestNode>
 }
 
 impl TestHead {
     fn new() -> Self {
         let mut test_head = TestHead {
-            head: Some(Rc::new(RefCell::new(TestNode::new("node3"))))
+            list: List::new(),
+            slab: Slab::new()
         };
-        test_head.push(Rc::new(RefCell::new(TestNode::new("node2"))));
-        test_head.push(Rc::new(RefCell::new(TestNode::new("node1"))));
+        for id in &["node3", "node2", "node1"] {
+            let node = test_head.slab.insert(TestNode::new(id));
+            test_head.list.push_front(&mut test_head.slab, node);
+        }
         test_head
     }
 
-    fn to_string(&self) -> String {
-        self.reduce(|acc: String, node| {
-            let mut acc = acc.clone();
-            acc.push_str(&format!(" {}", node.borrow().id));
+    fn describe(&self) -> String {
+        self.list.iter(&self.slab).fold("Nodes:".to_string(), |mut acc, (_, node)| {
+            acc.push_str(&format!(" {}", node.id));
             acc
-        }, "Nodes:".to_string())
-    }
-}
-
-impl HasHead for TestHead {
-    type Element = TestNode;
-
-    fn get_head(&self) -> Option<Rc<RefCell<TestNode>>> {
-        match self.head {
-            None => {None},
-            Some(ref head) => {
-                Some(Rc::clone(head))
-            }
-        }
-    }
-    fn set_head(&mut self, new_head: Option<Rc<RefCell<TestNode>>>) {
-        self.head = new_head
+        })
     }
 }
 
 #[derive(Debug)]
 struct TestNode {
     id: String,
-    next: Option<Rc<RefCell<TestNode>>>,
-    prev: Option<Weak<RefCell<TestNode>>>
+    next: Option<usize>,
+    prev: Option<usize>
 }
 
 impl TestNode {
@@ -61,22 +46,16 @@ impl TestNode {
 }
 
 impl Node for TestNode {
-    fn get_next(&self) -> Option<Rc<RefCell<TestNode>>> {
-        match self.next {
-            None => {None}
-            Some(ref next) => {Some(Rc::clone(next))}
-        }
+    fn get_next(&self) -> Option<usize> {
+        self.next
     }
-    fn set_next(&mut self, new_next: Option<Rc<RefCell<TestNode>>>) {
+    fn set_next(&mut self, new_next: Option<usize>) {
         self.next = new_next
     }
-    fn get_prev(&self) -> Option<Weak<RefCell<TestNode>>> {
-        match self.prev {
-            None => {None}
-            Some(ref prev) => {Some(Rc::downgrade(&Rc::clone(&prev.upgrade().unwrap())))}
-        }
+    fn get_prev(&self) -> Option<usize> {
+        self.prev
     }
-    fn set_prev(&mut self, new_prev: Option<Weak<RefCell<TestNode>>>) {
+    fn set_prev(&mut self, new_prev: Option<usize>) {
         self.prev = new_prev
     }
 }
@@ -84,38 +63,66 @@ impl Node for TestNode {
 #[test]
 fn test_structures() {
     let head = TestHead::new();
-    assert_eq!(head.to_string(), "Nodes: node1 node2 node3")
+    assert_eq!(head.describe(), "Nodes: node1 node2 node3")
 }
 
 #[test]
 fn pop_head() {
     let mut test_head = TestHead::new();
 
-    let old_head = test_head.pop_head();
-    let old_head = old_head.as_ref().unwrap().borrow();
+    let old_head = test_head.list.pop_front(&mut test_head.slab).unwrap();
+    let new_head = test_head.list.head().unwrap();
 
-    let new_head = test_head.head;
-    let new_head = new_head.as_ref().unwrap().borrow();
+    assert_eq!(test_head.slab[old_head].id, "node1");
+    assert_eq!(test_head.slab[new_head].id, "node2");
 
-    assert_eq!(old_head.id, "node1");
-    assert_eq!(new_head.id, "node2");
-
-    assert!(old_head.next.is_none());
-    assert!(new_head.prev.is_none());
+    assert!(test_head.slab[old_head].next.is_none());
+    assert!(test_head.slab[new_head].prev.is_none());
 }
 
 #[test]
 fn remove() {
-    let test_head = TestHead::new();
+    let mut test_head = TestHead::new();
     let node_to_remove = {
-        let head = test_head.get_head();
-        let head = head.as_ref().unwrap().borrow();
-
-        let node_to_remove = &head.next;
-        Rc::clone(node_to_remove.as_ref().unwrap())
+        let head = test_head.list.head().unwrap();
+        test_head.slab[head].next.unwrap()
     };
 
-    node_to_remove.borrow_mut().remove();
+    test_head.list.remove(&mut test_head.slab, node_to_remove);
+
+    assert_eq!(test_head.describe(), "Nodes: node1 node3");
+}
+
+#[test]
+fn push_back_and_insert_after() {
+    let mut test_head = TestHead::new();
 
-    assert_eq!(test_head.to_string(), "Nodes: node1 node3");
+    let node4 = test_head.slab.insert(TestNode::new("node4"));
+    test_head.list.push_back(&mut test_head.slab, node4);
+    assert_eq!(test_head.describe(), "Nodes: node1 node2 node3 node4");
+
+    let head = test_head.list.head().unwrap();
+    let node5 = test_head.slab.insert(TestNode::new("node5"));
+    test_head.list.insert_after(&mut test_head.slab, head, node5);
+    assert_eq!(test_head.describe(), "Nodes: node1 node5 node2 node3 node4");
+
+    let reversed: Vec<&str> = test_head.list.iter(&test_head.slab).rev()
+        .map(|(_, node)| node.id.as_str()).collect();
+    assert_eq!(reversed, vec!["node4", "node3", "node2", "node5", "node1"]);
+}
+
+#[test]
+fn slab_reuses_freed_slots() {
+    let mut slab = Slab::new();
+    let first = slab.insert("first");
+    let second = slab.insert("second");
+
+    assert_eq!(slab.remove(first), "first");
+    assert_eq!(slab.len(), 1);
+    assert!(slab.get(first).is_none());
+
+    let third = slab.insert("third");
+    assert_eq!(third, first);
+    assert_eq!(slab[second], "second");
+    assert_eq!(slab[third], "third");
 }
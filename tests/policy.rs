@@ -0,0 +1,180 @@
+extern crate lfu_rs;
+
+use lfu_rs::{EvictionPolicy, EvictionReason, FifoPolicy, LFUCache, LfuDaPolicy, LfuPolicy, LruPolicy,
+             TieBreaking};
+
+fn keys(cache: &LFUCache<String, String>) -> Vec<&str> {
+    cache.keys().map(|key| key.as_str()).collect()
+}
+
+fn filled(mut cache: LFUCache<String, String>) -> LFUCache<String, String> {
+    cache.insert("key1".to_string(), "val1".to_string());
+    cache.insert("key2".to_string(), "val2".to_string());
+    cache.insert("key3".to_string(), "val3".to_string());
+    cache.get(&"key1".to_string());
+    cache.get(&"key1".to_string());
+    cache.get(&"key2".to_string());
+    cache
+}
+
+#[test]
+fn lfu_is_the_default() {
+    let mut cache = filled(LFUCache::with_policy(3, LfuPolicy::new()));
+    assert_eq!(keys(&cache), vec!["key3", "key2", "key1"]);
+    cache.insert("key4".to_string(), "val4".to_string());
+    assert_eq!(keys(&cache), vec!["key4", "key2", "key1"]);
+
+    let cache = filled(LFUCache::new(3));
+    assert_eq!(keys(&cache), vec!["key3", "key2", "key1"]);
+}
+
+// key1 to key4 are inserted in order, then key2 and key1 are used
+fn tied(policy: LfuPolicy) -> LFUCache<String, String> {
+    let mut cache = LFUCache::with_policy(4, policy);
+    for i in 1..5 {
+        cache.insert(format!("key{}", i), format!("val{}", i));
+    }
+    cache.get(&"key2".to_string());
+    cache.get(&"key1".to_string());
+    cache
+}
+
+#[test]
+fn tie_breaking() {
+    assert_eq!(LfuPolicy::new().tie_breaking(), TieBreaking::MostRecentlyUsed);
+
+    let mut cache = tied(LfuPolicy::with_tie_breaking(TieBreaking::MostRecentlyUsed));
+    assert_eq!(keys(&cache), vec!["key4", "key3", "key1", "key2"]);
+    cache.insert("key5".to_string(), "val5".to_string());
+    assert_eq!(keys(&cache), vec!["key5", "key3", "key1", "key2"]);
+
+    let mut cache = tied(LfuPolicy::with_tie_breaking(TieBreaking::LeastRecentlyUsed));
+    assert_eq!(keys(&cache), vec!["key3", "key4", "key2", "key1"]);
+    cache.insert("key5".to_string(), "val5".to_string());
+    assert_eq!(keys(&cache), vec!["key4", "key5", "key2", "key1"]);
+
+    let mut cache = tied(LfuPolicy::with_tie_breaking(TieBreaking::FirstInFirstOut));
+    assert_eq!(keys(&cache), vec!["key3", "key4", "key1", "key2"]);
+    cache.insert("key5".to_string(), "val5".to_string());
+    assert_eq!(keys(&cache), vec!["key4", "key5", "key1", "key2"]);
+    cache.get(&"key4".to_string());
+    assert_eq!(keys(&cache), vec!["key5", "key1", "key2", "key4"]);
+}
+
+#[test]
+fn tie_breaking_when_aging() {
+    for &(tie_breaking, order) in &[
+        (TieBreaking::MostRecentlyUsed, ["key3", "key2", "key1"]),
+        (TieBreaking::LeastRecentlyUsed, ["key3", "key2", "key1"]),
+        (TieBreaking::FirstInFirstOut, ["key1", "key2", "key3"])
+    ] {
+        let mut cache = LFUCache::with_policy(3, LfuPolicy::with_tie_breaking(tie_breaking));
+        cache.insert("key1".to_string(), "val1".to_string());
+        cache.insert("key2".to_string(), "val2".to_string());
+        cache.insert("key3".to_string(), "val3".to_string());
+        cache.get(&"key1".to_string());
+        cache.get(&"key1".to_string());
+        cache.get(&"key2".to_string());
+
+        cache.age();
+        assert_eq!(keys(&cache), order, "{:?}", tie_breaking);
+    }
+}
+
+#[test]
+fn lfu_da_tie_breaking() {
+    let policy = LfuDaPolicy::with_tie_breaking(TieBreaking::LeastRecentlyUsed);
+    assert_eq!(policy.tie_breaking(), TieBreaking::LeastRecentlyUsed);
+    let mut cache = LFUCache::with_policy(2, policy);
+    cache.insert("key1".to_string(), "val1".to_string());
+    cache.insert("key2".to_string(), "val2".to_string());
+    cache.insert("key3".to_string(), "val3".to_string());
+    assert_eq!(keys(&cache), vec!["key2", "key3"]);
+}
+
+#[test]
+fn lru() {
+    let mut cache = filled(LFUCache::with_policy(3, LruPolicy::new()));
+    assert_eq!(keys(&cache), vec!["key3", "key1", "key2"]);
+
+    cache.get(&"key3".to_string());
+    cache.insert("key4".to_string(), "val4".to_string());
+    assert_eq!(keys(&cache), vec!["key2", "key3", "key4"]);
+
+    // frequencies are still counted
+    assert_eq!(cache.frequency(&"key2".to_string()), Some(2));
+    assert_eq!(format!("{}", cache), "Count 2: val2 val3\nCount 1: val4\n");
+}
+
+#[test]
+fn fifo() {
+    let mut cache = filled(LFUCache::with_policy(3, FifoPolicy::new()));
+    assert_eq!(keys(&cache), vec!["key1", "key2", "key3"]);
+
+    cache.insert("key4".to_string(), "val4".to_string());
+    assert_eq!(keys(&cache), vec!["key2", "key3", "key4"]);
+    assert_eq!(cache.pop_lfu(), Some(("key2".to_string(), "val2".to_string())));
+    assert_eq!(cache.pop_mfu(), Some(("key4".to_string(), "val4".to_string())));
+
+    cache.entry("key5".to_string()).or_insert("val5".to_string());
+    assert_eq!(keys(&cache), vec!["key3", "key5"]);
+}
+
+// Evicts the most recently inserted key, keeping keys in a Vec to show
+// that a policy can be built without the crate's lists
+#[derive(Default)]
+struct NewestFirst {
+    order: Vec<usize>
+}
+
+impl NewestFirst {
+    fn position(&self, node: usize) -> usize {
+        self.order.iter().position(|&n| n == node).expect("known node")
+    }
+}
+
+impl EvictionPolicy for NewestFirst {
+    fn on_insert(&mut self, node: usize) {
+        self.order.insert(0, node);
+    }
+
+    fn on_access(&mut self, _: usize) {}
+
+    fn on_remove(&mut self, node: usize, _: EvictionReason) {
+        let position = self.position(node);
+        self.order.remove(position);
+    }
+
+    fn choose_victim(&self) -> Option<usize> {
+        self.order.first().cloned()
+    }
+
+    fn last(&self) -> Option<usize> {
+        self.order.last().cloned()
+    }
+
+    fn next(&self, node: usize) -> Option<usize> {
+        self.order.get(self.position(node) + 1).cloned()
+    }
+
+    fn prev(&self, node: usize) -> Option<usize> {
+        match self.position(node) {
+            0 => None,
+            position => Some(self.order[position - 1])
+        }
+    }
+}
+
+#[test]
+fn custom_policy() {
+    let mut cache = filled(LFUCache::with_policy(3, NewestFirst::default()));
+    assert_eq!(keys(&cache), vec!["key3", "key2", "key1"]);
+
+    let result = cache.insert_with_eviction("key4".to_string(), "val4".to_string());
+    assert_eq!(result.evicted, vec![("key3".to_string(), "val3".to_string())]);
+    assert_eq!(keys(&cache), vec!["key4", "key2", "key1"]);
+    assert_eq!(keys(&cache).into_iter().rev().collect::<Vec<_>>(), vec!["key1", "key2", "key4"]);
+
+    cache.remove(&"key2".to_string());
+    assert_eq!(keys(&cache), vec!["key4", "key1"]);
+}
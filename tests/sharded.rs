@@ -0,0 +1,96 @@
+extern crate lfu_rs;
+
+use lfu_rs::ShardedLFUCache;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn capacity_is_divided_across_shards() {
+    let cache: ShardedLFUCache<usize, usize> = ShardedLFUCache::new(10, 4);
+
+    let capacities: Vec<usize> = cache.shard_stats().iter().map(|s| s.capacity).collect();
+    assert_eq!(cache.shard_count(), 4);
+    assert_eq!(capacities, vec![3, 3, 2, 2]);
+}
+
+#[test]
+fn insert_get_remove() {
+    let cache: ShardedLFUCache<String, String> = ShardedLFUCache::new(10, 2);
+    assert_eq!(cache.insert("key1".to_string(), "val1".to_string()), None);
+    assert_eq!(
+        cache.insert("key1".to_string(), "val2".to_string()),
+        Some("val1".to_string())
+    );
+    assert_eq!(cache.get(&"key1".to_string()), Some("val2".to_string()));
+    assert_eq!(cache.remove(&"key1".to_string()), Some("val2".to_string()));
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn evictions_are_counted_per_shard() {
+    let cache: ShardedLFUCache<usize, usize> = ShardedLFUCache::new(8, 4);
+
+    for i in 0..100 {
+        cache.insert(i, i);
+    }
+
+    let stats = cache.shard_stats();
+    assert_eq!(cache.len(), 8);
+    assert!(stats.iter().all(|s| s.len == s.capacity));
+    assert_eq!(stats.iter().map(|s| s.evictions).sum::<usize>(), 92);
+}
+
+#[test]
+fn shared_between_threads() {
+    let cache: Arc<ShardedLFUCache<usize, usize>> = Arc::new(ShardedLFUCache::new(64, 8));
+
+    let handles: Vec<_> = (0..4).map(|t| {
+        let cache = Arc::clone(&cache);
+        thread::spawn(move || {
+            for i in 0..1000 {
+                let key = t * 1000 + i;
+                cache.insert(key, key);
+                if let Some(value) = cache.get(&key) {
+                    assert_eq!(value, key);
+                }
+            }
+        })
+    }).collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let stats = cache.shard_stats();
+    assert!(stats.iter().all(|s| s.len <= s.capacity));
+    assert_eq!(stats.iter().map(|s| s.len + s.evictions).sum::<usize>(), 4000);
+}
+
+#[test]
+fn set_capacity() {
+    let cache: ShardedLFUCache<usize, usize> = ShardedLFUCache::new(8, 4);
+    for i in 0..8 {
+        cache.insert(i, i);
+    }
+    let len = cache.len();
+
+    let evicted = cache.set_capacity(4);
+    let stats = cache.shard_stats();
+    assert_eq!(cache.capacity(), 4);
+    assert!(stats.iter().all(|s| s.capacity == 1 && s.len <= 1));
+    assert_eq!(evicted.len(), len - cache.len());
+    assert_eq!(stats.iter().map(|s| s.evictions).sum::<usize>(), 8 - cache.len());
+}
+
+#[test]
+#[should_panic(expected = "without room for a key")]
+fn fewer_keys_than_shards() {
+    let _: ShardedLFUCache<usize, usize> = ShardedLFUCache::new(2, 4);
+}
+
+#[test]
+#[should_panic(expected = "without room for a key")]
+fn shrinking_below_shard_count() {
+    let cache: ShardedLFUCache<usize, usize> = ShardedLFUCache::new(8, 4);
+    cache.set_capacity(3);
+}
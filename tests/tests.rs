@@ -207,3 +207,127 @@ fn overfill() {
         )
     );
 }
+
+#[test]
+fn churn() {
+    let size = 10;
+    let mut cache: LFUCache<usize, usize> = LFUCache::new(size);
+
+    for i in 0..1000 {
+        cache.insert(i, i);
+        cache.get(&(i / 2));
+        if i % 3 == 2 {
+            cache.remove(&(i - 1));
+        }
+    }
+
+    assert!(cache.len() <= size);
+    assert_eq!(cache.get(&999), Some(&999));
+}
+
+#[test]
+fn peek_does_not_change_frequency() {
+    let mut cache: LFUCache<String, String> = LFUCache::new(2);
+    cache.insert("key1".to_string(), "val1".to_string());
+    cache.insert("key2".to_string(), "val2".to_string());
+    cache.get(&"key1".to_string());
+
+    assert_eq!(cache.peek(&"key2".to_string()), Some(&"val2".to_string()));
+    assert_eq!(cache.peek(&"key3".to_string()), None);
+    assert!(cache.contains_key(&"key2".to_string()));
+    assert!(!cache.contains_key(&"key3".to_string()));
+    assert_eq!(cache.frequency(&"key1".to_string()), Some(2));
+    assert_eq!(cache.frequency(&"key2".to_string()), Some(1));
+    assert_eq!(cache.frequency(&"key3".to_string()), None);
+    assert_eq!(cache.to_string(), "Count 1: val2\nCount 2: val1\n");
+
+    // key2 is still the least frequently used key
+    cache.insert("key3".to_string(), "val3".to_string());
+    assert!(!cache.contains_key(&"key2".to_string()));
+}
+
+#[test]
+fn mutate_in_place() {
+    let mut cache: LFUCache<String, usize> = LFUCache::new(10);
+    cache.insert("counter".to_string(), 0);
+    cache.insert("other".to_string(), 0);
+
+    *cache.get_mut(&"counter".to_string()).unwrap() += 1;
+    assert_eq!(cache.frequency(&"counter".to_string()), Some(2));
+
+    *cache.peek_mut(&"other".to_string()).unwrap() += 5;
+    assert_eq!(cache.frequency(&"other".to_string()), Some(1));
+
+    assert!(cache.get_mut(&"missing".to_string()).is_none());
+    assert!(cache.peek_mut(&"missing".to_string()).is_none());
+    assert_eq!(cache.to_string(), "Count 1: 5\nCount 2: 1\n");
+}
+
+#[test]
+fn values_without_display() {
+    #[derive(Debug, PartialEq)]
+    struct Blob(Vec<u8>);
+
+    let mut cache: LFUCache<String, Blob> = LFUCache::new(2);
+    cache.insert("key1".to_string(), Blob(vec![1, 2, 3]));
+    cache.insert("key2".to_string(), Blob(vec![4]));
+    cache.get(&"key1".to_string());
+
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.remove(&"key2".to_string()), Some(Blob(vec![4])));
+    assert_eq!(format!("{:?}", cache), "{\"key1\": Blob([1, 2, 3])}");
+}
+
+#[test]
+fn peek_and_pop_lfu_and_mfu() {
+    let mut cache: LFUCache<String, String> = LFUCache::new(10);
+    assert!(cache.peek_lfu().is_none());
+    assert!(cache.pop_mfu().is_none());
+
+    for i in 0..4 {
+        cache.insert(format!("key{}", i), format!("val{}", i));
+    }
+    cache.get(&"key0".to_string());
+    cache.get(&"key0".to_string());
+    cache.get(&"key1".to_string());
+
+    assert_eq!(cache.peek_lfu(), Some((&"key3".to_string(), &"val3".to_string(), 1)));
+    assert_eq!(cache.peek_mfu(), Some((&"key0".to_string(), &"val0".to_string(), 3)));
+    assert_eq!(cache.frequency(&"key3".to_string()), Some(1));
+
+    assert_eq!(cache.pop_lfu(), Some(("key3".to_string(), "val3".to_string())));
+    assert_eq!(cache.pop_mfu(), Some(("key0".to_string(), "val0".to_string())));
+    assert_eq!(cache.to_string(), "Count 1: val2\nCount 2: val1\n");
+
+    assert_eq!(cache.pop_lfu(), Some(("key2".to_string(), "val2".to_string())));
+    assert_eq!(cache.pop_lfu(), Some(("key1".to_string(), "val1".to_string())));
+    assert!(cache.pop_lfu().is_none());
+    assert_eq!(cache.to_string(), "<empty>");
+}
+
+#[test]
+fn set_capacity() {
+    let mut cache: LFUCache<String, String> = LFUCache::new(4);
+    assert_eq!(cache.capacity(), 4);
+
+    for i in 0..4 {
+        cache.insert(format!("key{}", i), format!("val{}", i));
+    }
+    cache.get(&"key0".to_string());
+
+    assert!(cache.set_capacity(6).is_empty());
+    cache.insert("key4".to_string(), "val4".to_string());
+    assert_eq!(cache.len(), 5);
+
+    let evicted = cache.set_capacity(2);
+    assert_eq!(evicted, vec![
+        ("key4".to_string(), "val4".to_string()),
+        ("key3".to_string(), "val3".to_string()),
+        ("key2".to_string(), "val2".to_string()),
+    ]);
+    assert_eq!(cache.capacity(), 2);
+    assert_eq!(cache.to_string(), "Count 1: val1\nCount 2: val0\n");
+
+    cache.insert("key5".to_string(), "val5".to_string());
+    assert_eq!(cache.len(), 2);
+}
@@ -0,0 +1,173 @@
+extern crate lfu_rs;
+
+use lfu_rs::{ConcurrentLFUCache, EvictionReason, LFUCache, ManualClock};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn cache_with_clock(max_size: usize) -> (LFUCache<String, String>, ManualClock) {
+    let clock = ManualClock::new();
+    let mut cache = LFUCache::new(max_size);
+    cache.set_clock(clock.clone());
+    (cache, clock)
+}
+
+fn secs(secs: u64) -> Duration {
+    Duration::from_secs(secs)
+}
+
+#[test]
+fn expired_keys_are_absent() {
+    let (mut cache, clock) = cache_with_clock(10);
+    cache.insert_with_ttl("key1".to_string(), "val1".to_string(), secs(5));
+    cache.insert("key2".to_string(), "val2".to_string());
+
+    clock.advance(secs(4));
+    assert_eq!(cache.peek(&"key1".to_string()), Some(&"val1".to_string()));
+
+    clock.advance(secs(1));
+    assert_eq!(cache.peek(&"key1".to_string()), None);
+    assert!(!cache.contains_key(&"key1".to_string()));
+    assert_eq!(cache.frequency(&"key1".to_string()), None);
+
+    // peeking leaves the expired entry in place, looking it up removes it
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(&"key1".to_string()), None);
+    assert_eq!(cache.len(), 1);
+
+    assert_eq!(cache.get(&"key2".to_string()), Some(&"val2".to_string()));
+}
+
+#[test]
+fn default_ttl() {
+    let (mut cache, clock) = cache_with_clock(10);
+    assert_eq!(cache.default_ttl(), None);
+    cache.set_default_ttl(Some(secs(10)));
+    assert_eq!(cache.default_ttl(), Some(secs(10)));
+
+    cache.insert("key1".to_string(), "val1".to_string());
+    cache.entry("key2".to_string()).or_insert("val2".to_string());
+    cache.insert_with_ttl("key3".to_string(), "val3".to_string(), secs(20));
+
+    clock.advance(secs(10));
+    assert_eq!(cache.get(&"key1".to_string()), None);
+    assert_eq!(cache.get(&"key2".to_string()), None);
+    assert_eq!(cache.get(&"key3".to_string()), Some(&"val3".to_string()));
+}
+
+#[test]
+fn replacing_resets_expiry() {
+    let (mut cache, clock) = cache_with_clock(10);
+    cache.set_default_ttl(Some(secs(10)));
+    cache.insert_with_ttl("key1".to_string(), "val1".to_string(), secs(5));
+
+    clock.advance(secs(4));
+    assert_eq!(cache.insert("key1".to_string(), "val2".to_string()), Some("val1".to_string()));
+
+    clock.advance(secs(9));
+    assert_eq!(cache.get(&"key1".to_string()), Some(&"val2".to_string()));
+
+    // an expired key is not replaced, it is inserted afresh
+    clock.advance(secs(1));
+    assert_eq!(cache.insert("key1".to_string(), "val3".to_string()), None);
+    assert_eq!(cache.frequency(&"key1".to_string()), Some(1));
+}
+
+#[test]
+fn expired_keys_are_evicted_first() {
+    let evictions = Arc::new(Mutex::new(Vec::new()));
+    let (mut cache, clock) = cache_with_clock(2);
+    let recorder = Arc::clone(&evictions);
+    cache.set_eviction_listener(move |key: &String, _: &String, _, reason| {
+        recorder.lock().unwrap().push((key.clone(), reason));
+    });
+
+    cache.insert("key1".to_string(), "val1".to_string());
+    cache.insert_with_ttl("key2".to_string(), "val2".to_string(), secs(5));
+    cache.get(&"key2".to_string());
+    cache.get(&"key2".to_string());
+
+    clock.advance(secs(5));
+    let result = cache.insert_with_eviction("key3".to_string(), "val3".to_string());
+    assert_eq!(result.evicted, vec![("key2".to_string(), "val2".to_string())]);
+    assert_eq!(*evictions.lock().unwrap(), vec![("key2".to_string(), EvictionReason::Expired)]);
+
+    assert_eq!(cache.get(&"key1".to_string()), Some(&"val1".to_string()));
+    assert_eq!(cache.get(&"key3".to_string()), Some(&"val3".to_string()));
+}
+
+#[test]
+fn purge_expired() {
+    let (mut cache, clock) = cache_with_clock(10);
+    cache.insert_with_ttl("key1".to_string(), "val1".to_string(), secs(3));
+    cache.insert_with_ttl("key2".to_string(), "val2".to_string(), secs(1));
+    cache.insert_with_ttl("key3".to_string(), "val3".to_string(), secs(5));
+    cache.insert("key4".to_string(), "val4".to_string());
+
+    assert_eq!(cache.purge_expired(), vec![]);
+
+    clock.advance(secs(3));
+    assert_eq!(cache.purge_expired(), vec![
+        ("key2".to_string(), "val2".to_string()),
+        ("key1".to_string(), "val1".to_string())
+    ]);
+    assert_eq!(cache.len(), 2);
+
+    // removing an entry also drops its expiry
+    cache.remove(&"key3".to_string());
+    clock.advance(secs(2));
+    assert_eq!(cache.purge_expired(), vec![]);
+    assert_eq!(cache.len(), 1);
+}
+
+#[test]
+fn pop_lfu_skips_expired() {
+    let (mut cache, clock) = cache_with_clock(10);
+    cache.insert_with_ttl("key1".to_string(), "val1".to_string(), secs(1));
+    cache.insert("key2".to_string(), "val2".to_string());
+    cache.get(&"key2".to_string());
+
+    clock.advance(secs(1));
+    assert_eq!(cache.pop_lfu(), Some(("key2".to_string(), "val2".to_string())));
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn peek_skips_expired() {
+    let (mut cache, clock) = cache_with_clock(10);
+    cache.insert_with_ttl("key1".to_string(), "val1".to_string(), secs(1));
+    cache.insert("key2".to_string(), "val2".to_string());
+    cache.insert_with_ttl("key3".to_string(), "val3".to_string(), secs(1));
+    cache.get("key2");
+    cache.get("key3");
+    cache.get("key3");
+
+    clock.advance(secs(1));
+    assert_eq!(cache.peek_lfu(), Some((&"key2".to_string(), &"val2".to_string(), 2)));
+    assert_eq!(cache.peek_mfu(), Some((&"key2".to_string(), &"val2".to_string(), 2)));
+    assert_eq!(cache.peek("key1"), None);
+    assert_eq!(cache.peek("key3"), None);
+}
+
+#[test]
+fn huge_ttl_never_expires() {
+    let forever = Duration::from_secs(u64::max_value());
+    let (mut cache, clock) = cache_with_clock(10);
+    cache.insert_with_ttl("key1".to_string(), "val1".to_string(), forever);
+    cache.set_default_ttl(Some(forever));
+    cache.insert("key2".to_string(), "val2".to_string());
+
+    clock.advance(secs(1000 * 365 * 24 * 60 * 60));
+    assert!(cache.contains_key("key1"));
+    assert!(cache.contains_key("key2"));
+    assert!(cache.purge_expired().is_empty());
+}
+
+#[test]
+fn concurrent_cache() {
+    let cache: ConcurrentLFUCache<String, String> = ConcurrentLFUCache::new(10);
+    cache.insert_with_ttl("key1".to_string(), "val1".to_string(), Duration::from_millis(0));
+    cache.insert("key2".to_string(), "val2".to_string());
+
+    assert_eq!(cache.purge_expired(), vec![("key1".to_string(), "val1".to_string())]);
+    assert_eq!(cache.get(&"key2".to_string()), Some("val2".to_string()));
+}
@@ -0,0 +1,96 @@
+extern crate lfu_rs;
+
+use lfu_rs::{Entry, InsertResult, LFUCache};
+
+fn byte_cache(max_weight: usize) -> LFUCache<String, Vec<u8>> {
+    LFUCache::with_weigher(max_weight, |_: &String, value: &Vec<u8>| value.len())
+}
+
+#[test]
+fn evicts_until_weight_fits() {
+    let mut cache = byte_cache(10);
+    cache.insert("small1".to_string(), vec![0; 2]);
+    cache.insert("small2".to_string(), vec![0; 3]);
+    cache.insert("small3".to_string(), vec![0; 4]);
+    cache.get(&"small1".to_string());
+    assert_eq!(cache.weight(), 9);
+
+    // needs to evict both small3 and small2, but not small1
+    let result = cache.insert_with_eviction("big".to_string(), vec![0; 8]);
+    assert_eq!(result.replaced, None);
+    assert_eq!(result.evicted, vec![
+        ("small3".to_string(), vec![0; 4]),
+        ("small2".to_string(), vec![0; 3]),
+    ]);
+    assert_eq!(cache.weight(), 10);
+    assert_eq!(cache.len(), 2);
+}
+
+#[test]
+fn entry_heavier_than_capacity_is_not_kept() {
+    let mut cache = byte_cache(10);
+    cache.insert("small".to_string(), vec![0; 2]);
+
+    let result = cache.insert_with_eviction("huge".to_string(), vec![0; 11]);
+    assert_eq!(result, InsertResult {
+        replaced: None,
+        evicted: vec![("huge".to_string(), vec![0; 11])]
+    });
+    assert!(cache.contains_key(&"small".to_string()));
+    assert!(!cache.contains_key(&"huge".to_string()));
+    assert_eq!(cache.weight(), 2);
+
+    // replacing a value with one that is too heavy drops the key
+    assert_eq!(cache.insert("small".to_string(), vec![1; 11]), Some(vec![0; 2]));
+    assert!(cache.is_empty());
+    assert_eq!(cache.weight(), 0);
+}
+
+#[test]
+fn replacing_with_heavier_value() {
+    let mut cache = byte_cache(10);
+    cache.insert("key1".to_string(), vec![0; 4]);
+    cache.insert("key2".to_string(), vec![0; 4]);
+
+    let result = cache.insert_with_eviction("key2".to_string(), vec![0; 7]);
+    assert_eq!(result.replaced, Some(vec![0; 4]));
+    assert_eq!(result.evicted, vec![("key1".to_string(), vec![0; 4])]);
+    assert_eq!(cache.weight(), 7);
+}
+
+#[test]
+fn entries() {
+    let mut cache = byte_cache(10);
+    cache.insert("key1".to_string(), vec![0; 6]);
+    cache.entry("key2".to_string()).or_insert_with(|| vec![0; 6]);
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.weight(), 6);
+
+    if let Entry::Occupied(mut entry) = cache.entry("key2".to_string()) {
+        entry.insert(vec![0; 12]);
+    }
+    assert!(cache.contains_key(&"key2".to_string()));
+    assert_eq!(cache.weight(), 12);
+}
+
+#[test]
+fn shrinking_capacity() {
+    let mut cache = byte_cache(10);
+    cache.insert("key1".to_string(), vec![0; 5]);
+    cache.insert("key2".to_string(), vec![0; 5]);
+    cache.get(&"key2".to_string());
+
+    assert_eq!(cache.set_capacity(6), vec![("key1".to_string(), vec![0; 5])]);
+    assert_eq!(cache.capacity(), 6);
+    assert_eq!(cache.weight(), 5);
+}
+
+#[test]
+fn unweighted_capacity_counts_entries() {
+    let mut cache: LFUCache<usize, usize> = LFUCache::new(3);
+    for i in 0..5 {
+        cache.insert(i, i);
+    }
+    assert_eq!(cache.weight(), 3);
+    assert_eq!(cache.len(), 3);
+}